@@ -1,9 +1,19 @@
 /// SECURITY: Audit Logging & Security Monitoring
 /// Comprehensive security event tracking without sensitive data exposure
 
-use anyhow::Result;
+use crate::metrics::{MetricLabels, MetricsCollector};
+use anyhow::{anyhow, Result};
+use arc_swap::ArcSwap;
+use rtrb::RingBuffer;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::net::UdpSocket;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
 use tracing::{info, warn, error, debug};
 use uuid::Uuid;
 
@@ -43,6 +53,9 @@ pub struct AuditLogEntry {
     pub client_id: Option<String>,
     pub method: Option<String>,
     pub resource: Option<String>,
+    /// Correlation id linking this audit record back to the request's debug log
+    /// line and any client-visible error, when the caller supplied one.
+    pub request_id: Option<String>,
     pub action: String,
     pub result: String,
     pub details: HashMap<String, String>,
@@ -61,6 +74,7 @@ impl AuditLogEntry {
             client_id: None,
             method: None,
             resource: None,
+            request_id: None,
             action,
             result: "pending".to_string(),
             details: HashMap::new(),
@@ -89,6 +103,11 @@ impl AuditLogEntry {
         self.client_id = Some(client_id);
         self
     }
+
+    pub fn with_request_id(mut self, request_id: String) -> Self {
+        self.request_id = Some(request_id);
+        self
+    }
     
     pub fn with_method(mut self, method: String) -> Self {
         self.method = Some(method);
@@ -147,9 +166,11 @@ impl AuditLogEntry {
         sanitized
     }
     
-    /// Log this audit entry using structured logging
-    pub fn log(&self) {
-        let log_data = serde_json::json!({
+    /// Renders the already-sanitized fields as JSON, shared by every sink
+    /// (tracing, syslog, the rotating file, `ChannelAuditSink`) so they all
+    /// agree on the same event shape.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
             "event_id": self.event_id,
             "event_type": self.event_type,
             "severity": self.severity,
@@ -157,13 +178,19 @@ impl AuditLogEntry {
             "client_id": self.client_id,
             "method": self.method,
             "resource": self.resource,
+            "request_id": self.request_id,
             "action": self.action,
             "result": self.result,
             "details": self.details,
             "risk_score": self.risk_score,
             "timestamp": self.timestamp.to_rfc3339()
-        });
-        
+        })
+    }
+
+    /// Log this audit entry using structured logging
+    pub fn log(&self) {
+        let log_data = self.to_json();
+
         match self.severity {
             EventSeverity::Critical => {
                 error!(target: "audit", "{}", log_data);
@@ -184,205 +211,667 @@ impl AuditLogEntry {
     }
 }
 
+/// Pluggable destination for audit events, in addition to the in-process
+/// buffer/log line `SecurityAuditor::log_event` always writes. `emit` must be
+/// non-blocking - it runs on the request path - so implementations that ship
+/// events elsewhere (a socket, an external queue) should hand off over a
+/// bounded channel and drop under backpressure rather than stall.
+pub trait AuditSink: Send + Sync {
+    fn emit(&self, entry: &AuditLogEntry);
+}
+
+/// Ships each audit entry as JSON to an external pipeline (e.g. a Kafka-style
+/// producer) over a bounded channel drained by a background task. When the
+/// channel is full the event is dropped and counted rather than blocking the
+/// request path; `dropped_count` lets operators notice sustained backpressure.
+pub struct ChannelAuditSink {
+    sender: tokio::sync::mpsc::Sender<serde_json::Value>,
+    dropped: std::sync::atomic::AtomicU64,
+}
+
+impl ChannelAuditSink {
+    /// Spawns a background task that drains the channel and passes each
+    /// event to `forward` (e.g. a closure doing the actual network I/O).
+    pub fn new<F, Fut>(buffer_size: usize, forward: F) -> Self
+    where
+        F: Fn(serde_json::Value) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = ()> + Send + 'static,
+    {
+        let (sender, mut receiver) = tokio::sync::mpsc::channel(buffer_size);
+        tokio::spawn(async move {
+            while let Some(event) = receiver.recv().await {
+                forward(event).await;
+            }
+        });
+        Self {
+            sender,
+            dropped: std::sync::atomic::AtomicU64::new(0),
+        }
+    }
+
+    /// Number of events dropped so far because the channel was full.
+    pub fn dropped_count(&self) -> u64 {
+        self.dropped.load(std::sync::atomic::Ordering::Relaxed)
+    }
+}
+
+impl AuditSink for ChannelAuditSink {
+    fn emit(&self, entry: &AuditLogEntry) {
+        let payload = entry.to_json();
+
+        if self.sender.try_send(payload).is_err() {
+            self.dropped.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            warn!("Audit sink backpressure: dropped event {}", entry.event_id);
+        }
+    }
+}
+
+/// Wraps the same tracing call `AuditLogEntry::log` always makes. Not part of
+/// `SecurityAuditor`'s default sink list - that call is unconditional so
+/// adding this sink too would double-log - but exposed for callers that want
+/// to compose the tracing path uniformly with the other `AuditSink` impls.
+pub struct TracingAuditSink;
+
+impl AuditSink for TracingAuditSink {
+    fn emit(&self, entry: &AuditLogEntry) {
+        entry.log();
+    }
+}
+
+/// RFC 5424 severity levels, in order Emergency(0) through Debug(7).
+fn syslog_severity(severity: &EventSeverity) -> u8 {
+    match severity {
+        EventSeverity::Critical => 2,
+        EventSeverity::High => 3,
+        EventSeverity::Medium => 4,
+        EventSeverity::Low => 5,
+        EventSeverity::Info => 6,
+    }
+}
+
+/// Ships each audit entry to a syslog collector as an RFC 5424 message over
+/// UDP. Facility is fixed at `local0` (16), the conventional facility for an
+/// application's own events. Sending is fire-and-forget: a dropped or
+/// unreachable UDP packet is logged and otherwise ignored rather than
+/// blocking the request path.
+pub struct SyslogAuditSink {
+    socket: UdpSocket,
+    address: String,
+    hostname: String,
+}
+
+const SYSLOG_FACILITY_LOCAL0: u8 = 16;
+
+impl SyslogAuditSink {
+    /// `address` is the syslog collector's `host:port` (RFC 5424 is typically
+    /// carried over UDP port 514).
+    pub fn new(address: impl Into<String>) -> Result<Self> {
+        let socket = UdpSocket::bind("0.0.0.0:0")
+            .map_err(|e| anyhow!("Failed to bind syslog UDP socket: {}", e))?;
+        let hostname = hostname_or_nilvalue();
+        Ok(Self { socket, address: address.into(), hostname })
+    }
+}
+
+impl AuditSink for SyslogAuditSink {
+    fn emit(&self, entry: &AuditLogEntry) {
+        let pri = SYSLOG_FACILITY_LOCAL0 * 8 + syslog_severity(&entry.severity);
+        let packet = format!(
+            "<{}>1 {} {} deepseek-mcp-bridge - {} - {}",
+            pri,
+            entry.timestamp.to_rfc3339(),
+            self.hostname,
+            entry.event_id,
+            entry.to_json(),
+        );
+
+        if let Err(e) = self.socket.send_to(packet.as_bytes(), &self.address) {
+            warn!("Failed to send audit event {} to syslog at {}: {}", entry.event_id, self.address, e);
+        }
+    }
+}
+
+/// Best-effort hostname lookup for the syslog `HOSTNAME` field; falls back to
+/// RFC 5424's nil value (`-`) rather than failing sink construction over it.
+fn hostname_or_nilvalue() -> String {
+    std::env::var("HOSTNAME")
+        .ok()
+        .filter(|h| !h.is_empty())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+/// Appends each audit entry as a JSON-lines record to a file, rotating to a
+/// timestamped sibling file once the current file exceeds `max_bytes` or a
+/// new UTC day begins - whichever comes first. Lets audit events survive on
+/// disk independently of whatever the app's own log destination is set to.
+pub struct RotatingFileAuditSink {
+    state: StdMutex<RotatingFileState>,
+    max_bytes: u64,
+}
+
+struct RotatingFileState {
+    path: PathBuf,
+    file: File,
+    bytes_written: u64,
+    opened_on: chrono::NaiveDate,
+}
+
+impl RotatingFileAuditSink {
+    pub fn new(path: impl Into<PathBuf>, max_bytes: u64) -> Result<Self> {
+        let path = path.into();
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| anyhow!("Failed to open audit log file {}: {}", path.display(), e))?;
+        let bytes_written = file.metadata().map(|m| m.len()).unwrap_or(0);
+
+        Ok(Self {
+            state: StdMutex::new(RotatingFileState {
+                path,
+                file,
+                bytes_written,
+                opened_on: chrono::Utc::now().date_naive(),
+            }),
+            max_bytes,
+        })
+    }
+
+    fn rotate(&self, state: &mut RotatingFileState) -> std::io::Result<()> {
+        let rotated_path = state.path.with_extension(format!("{}.jsonl", chrono::Utc::now().format("%Y%m%dT%H%M%SZ")));
+        std::fs::rename(&state.path, &rotated_path)?;
+        state.file = OpenOptions::new().create(true).append(true).open(&state.path)?;
+        state.bytes_written = 0;
+        state.opened_on = chrono::Utc::now().date_naive();
+        Ok(())
+    }
+}
+
+impl AuditSink for RotatingFileAuditSink {
+    fn emit(&self, entry: &AuditLogEntry) {
+        let mut state = match self.state.lock() {
+            Ok(guard) => guard,
+            Err(_) => {
+                warn!("Audit file sink mutex poisoned, dropping event {}", entry.event_id);
+                return;
+            }
+        };
+
+        let today = chrono::Utc::now().date_naive();
+        if today != state.opened_on || state.bytes_written >= self.max_bytes {
+            if let Err(e) = self.rotate(&mut state) {
+                warn!("Failed to rotate audit log file {}: {}", state.path.display(), e);
+            }
+        }
+
+        let line = format!("{}\n", entry.to_json());
+        match state.file.write_all(line.as_bytes()) {
+            Ok(()) => state.bytes_written += line.len() as u64,
+            Err(e) => warn!("Failed to write audit event {} to {}: {}", entry.event_id, state.path.display(), e),
+        }
+    }
+}
+
+/// Filter passed to `AuditStore::query`; every field left `None` matches
+/// anything, so `AuditQuery::default()` returns the full history.
+#[derive(Debug, Clone, Default)]
+pub struct AuditQuery {
+    pub client_id: Option<String>,
+    pub event_type: Option<SecurityEventType>,
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
+    pub until: Option<chrono::DateTime<chrono::Utc>>,
+    pub min_risk_score: Option<u32>,
+}
+
+impl AuditQuery {
+    fn matches(&self, entry: &AuditLogEntry) -> bool {
+        if let Some(client_id) = &self.client_id {
+            if entry.client_id.as_deref() != Some(client_id.as_str()) {
+                return false;
+            }
+        }
+        if let Some(event_type) = &self.event_type {
+            if std::mem::discriminant(event_type) != std::mem::discriminant(&entry.event_type) {
+                return false;
+            }
+        }
+        if let Some(since) = self.since {
+            if entry.timestamp < since {
+                return false;
+            }
+        }
+        if let Some(until) = self.until {
+            if entry.timestamp > until {
+                return false;
+            }
+        }
+        if let Some(min_risk_score) = self.min_risk_score {
+            if entry.risk_score < min_risk_score {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Backing storage for `SecurityAuditor`'s event history, swappable so
+/// forensic queries can run over durable storage instead of just whatever
+/// fits in memory. `recent`/`query` return newest-first.
+pub trait AuditStore: Send + Sync {
+    fn append(&mut self, entry: AuditLogEntry);
+    fn recent(&self, limit: usize) -> Vec<AuditLogEntry>;
+    fn query(&self, filter: &AuditQuery) -> Vec<AuditLogEntry>;
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// The original behavior: a capped ring buffer, oldest entry evicted once
+/// `max_size` is exceeded. Cheap and fast, but wiped on restart - the default
+/// backend unless a durable one is configured. Backed by a `VecDeque` rather
+/// than a `Vec` so eviction (`pop_front`) is O(1) instead of shifting every
+/// remaining element on every append past capacity.
+pub struct InMemoryAuditStore {
+    entries: VecDeque<AuditLogEntry>,
+    max_size: usize,
+}
+
+impl InMemoryAuditStore {
+    pub fn new(max_size: usize) -> Self {
+        Self { entries: VecDeque::new(), max_size }
+    }
+}
+
+impl AuditStore for InMemoryAuditStore {
+    fn append(&mut self, entry: AuditLogEntry) {
+        self.entries.push_back(entry);
+        if self.entries.len() > self.max_size {
+            self.entries.pop_front();
+        }
+    }
+
+    fn recent(&self, limit: usize) -> Vec<AuditLogEntry> {
+        self.entries.iter().rev().take(limit).cloned().collect()
+    }
+
+    fn query(&self, filter: &AuditQuery) -> Vec<AuditLogEntry> {
+        self.entries.iter().rev().filter(|entry| filter.matches(entry)).cloned().collect()
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+/// Durable backend over an embedded `sled` database, keyed by timestamp so
+/// iteration order matches insertion order. Unlike `InMemoryAuditStore` it
+/// never evicts - events survive a restart for later forensic review.
+pub struct SledAuditStore {
+    db: sled::Db,
+}
+
+impl SledAuditStore {
+    pub fn open(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        let db = sled::open(path.as_ref())
+            .map_err(|e| anyhow!("Failed to open sled audit store at {}: {}", path.as_ref().display(), e))?;
+        Ok(Self { db })
+    }
+
+    fn key_for(entry: &AuditLogEntry) -> Vec<u8> {
+        let mut key = entry.timestamp.timestamp_nanos_opt().unwrap_or(0).to_be_bytes().to_vec();
+        key.extend_from_slice(entry.event_id.as_bytes());
+        key
+    }
+}
+
+impl AuditStore for SledAuditStore {
+    fn append(&mut self, entry: AuditLogEntry) {
+        let key = Self::key_for(&entry);
+        match serde_json::to_vec(&entry) {
+            Ok(value) => {
+                if let Err(e) = self.db.insert(key, value) {
+                    warn!("Failed to persist audit event {} to sled: {}", entry.event_id, e);
+                }
+            }
+            Err(e) => warn!("Failed to serialize audit event {} for sled: {}", entry.event_id, e),
+        }
+    }
+
+    fn recent(&self, limit: usize) -> Vec<AuditLogEntry> {
+        self.db.iter().values()
+            .rev()
+            .filter_map(|v| v.ok())
+            .filter_map(|v| serde_json::from_slice(&v).ok())
+            .take(limit)
+            .collect()
+    }
+
+    fn query(&self, filter: &AuditQuery) -> Vec<AuditLogEntry> {
+        self.db.iter().values()
+            .rev()
+            .filter_map(|v| v.ok())
+            .filter_map(|v| serde_json::from_slice::<AuditLogEntry>(&v).ok())
+            .filter(|entry| filter.matches(entry))
+            .collect()
+    }
+
+    fn len(&self) -> usize {
+        self.db.len()
+    }
+}
+
+/// Writes every event through to both a fast `primary` (which serves all
+/// reads) and a durable `secondary`, so queries stay cheap while events still
+/// survive a restart via the secondary.
+pub struct WriteThroughAuditStore {
+    primary: Box<dyn AuditStore>,
+    secondary: Box<dyn AuditStore>,
+}
+
+impl WriteThroughAuditStore {
+    pub fn new(primary: Box<dyn AuditStore>, secondary: Box<dyn AuditStore>) -> Self {
+        Self { primary, secondary }
+    }
+}
+
+impl AuditStore for WriteThroughAuditStore {
+    fn append(&mut self, entry: AuditLogEntry) {
+        self.primary.append(entry.clone());
+        self.secondary.append(entry);
+    }
+
+    fn recent(&self, limit: usize) -> Vec<AuditLogEntry> {
+        self.primary.recent(limit)
+    }
+
+    fn query(&self, filter: &AuditQuery) -> Vec<AuditLogEntry> {
+        self.primary.query(filter)
+    }
+
+    fn len(&self) -> usize {
+        self.primary.len()
+    }
+}
+
+/// Risk score a `SecurityAuditor` with no explicit threshold set treats as
+/// alert-worthy, alongside `Critical`/`High` severity.
+const DEFAULT_NOTIFY_RISK_SCORE_THRESHOLD: u32 = 75;
+
+/// Fires for `Critical`/`High` severity events, or any event whose
+/// `risk_score` crosses `SecurityAuditor`'s configured threshold. `notify`
+/// runs synchronously from `log_event`, so - like `AuditSink::emit` -
+/// implementations that do real I/O should hand off rather than await.
+pub trait Notifier: Send + Sync {
+    fn notify(&self, entry: &AuditLogEntry);
+}
+
+/// Posts the sanitized entry as JSON to a webhook URL. Delivery runs on a
+/// background task fed by a bounded channel, mirroring `ChannelAuditSink`; a
+/// full channel means the alert is dropped and counted rather than stalling
+/// the request path.
+pub struct WebhookNotifier {
+    sender: tokio::sync::mpsc::Sender<serde_json::Value>,
+    dropped: AtomicU64,
+}
+
+impl WebhookNotifier {
+    pub fn new(url: String, buffer_size: usize) -> Self {
+        let (sender, mut receiver) = tokio::sync::mpsc::channel(buffer_size);
+        let http_client = reqwest::Client::new();
+        tokio::spawn(async move {
+            while let Some(payload) = receiver.recv().await {
+                if let Err(e) = http_client.post(&url).json(&payload).send().await {
+                    warn!("Failed to deliver security alert to webhook {}: {}", url, e);
+                }
+            }
+        });
+        Self { sender, dropped: AtomicU64::new(0) }
+    }
+
+    /// Number of alerts dropped so far because the channel was full.
+    pub fn dropped_count(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl Notifier for WebhookNotifier {
+    fn notify(&self, entry: &AuditLogEntry) {
+        if self.sender.try_send(entry.to_json()).is_err() {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            warn!("Security alert webhook backpressure: dropped event {}", entry.event_id);
+        }
+    }
+}
+
+/// Generic escape hatch: invokes an arbitrary closure synchronously for each
+/// qualifying event, e.g. to bridge into an in-process paging channel instead
+/// of an HTTP webhook.
+pub struct CallbackNotifier<F: Fn(&AuditLogEntry) + Send + Sync> {
+    callback: F,
+}
+
+impl<F: Fn(&AuditLogEntry) + Send + Sync> CallbackNotifier<F> {
+    pub fn new(callback: F) -> Self {
+        Self { callback }
+    }
+}
+
+impl<F: Fn(&AuditLogEntry) + Send + Sync> Notifier for CallbackNotifier<F> {
+    fn notify(&self, entry: &AuditLogEntry) {
+        (self.callback)(entry);
+    }
+}
+
+/// Caps `SecurityAuditor::last_notified` the same way `MAX_TRACKED_CLIENTS`
+/// caps `SecurityPatternDetector::client_patterns` - `client_id` comes
+/// straight from request context, so a client that cycles distinct ids could
+/// otherwise grow this map without bound.
+const MAX_TRACKED_NOTIFY_KEYS: usize = 10_000;
+
 /// Security audit logger with event buffering and analysis
 pub struct SecurityAuditor {
     enabled: bool,
-    buffer: Vec<AuditLogEntry>,
-    max_buffer_size: usize,
+    store: Box<dyn AuditStore>,
     security_patterns: SecurityPatternDetector,
+    sinks: Vec<Box<dyn AuditSink>>,
+    notifiers: Vec<Box<dyn Notifier>>,
+    notify_risk_score_threshold: u32,
+    notify_debounce_window: chrono::Duration,
+    last_notified: HashMap<(String, String), chrono::DateTime<chrono::Utc>>,
+    max_notify_keys: usize,
 }
 
 impl SecurityAuditor {
     pub fn new(enabled: bool) -> Self {
         Self {
             enabled,
-            buffer: Vec::new(),
-            max_buffer_size: 1000,
+            store: Box::new(InMemoryAuditStore::new(1000)),
             security_patterns: SecurityPatternDetector::new(),
+            sinks: Vec::new(),
+            notifiers: Vec::new(),
+            notify_risk_score_threshold: DEFAULT_NOTIFY_RISK_SCORE_THRESHOLD,
+            notify_debounce_window: chrono::Duration::minutes(5),
+            last_notified: HashMap::new(),
+            max_notify_keys: MAX_TRACKED_NOTIFY_KEYS,
         }
     }
-    
+
+    /// Swap the event-history backend, e.g. for a `SledAuditStore` or a
+    /// `WriteThroughAuditStore` wrapping one. Defaults to a 1000-entry
+    /// `InMemoryAuditStore` when never called.
+    pub fn with_store(mut self, store: Box<dyn AuditStore>) -> Self {
+        self.store = store;
+        self
+    }
+
+    /// Register an additional sink that every subsequently logged event is
+    /// also forwarded to, alongside the always-on in-process buffer and log
+    /// line.
+    pub fn with_sink(mut self, sink: Box<dyn AuditSink>) -> Self {
+        self.sinks.push(sink);
+        self
+    }
+
+    /// Register an additional real-time notifier, fired for `Critical`/`High`
+    /// severity events and any event crossing `notify_risk_score_threshold`.
+    pub fn with_notifier(mut self, notifier: Box<dyn Notifier>) -> Self {
+        self.notifiers.push(notifier);
+        self
+    }
+
+    /// Overrides the default risk score (`DEFAULT_NOTIFY_RISK_SCORE_THRESHOLD`)
+    /// above which an event triggers notifiers regardless of severity.
+    pub fn with_notify_risk_score_threshold(mut self, threshold: u32) -> Self {
+        self.notify_risk_score_threshold = threshold;
+        self
+    }
+
+    /// Overrides the default 5-minute debounce window within which the same
+    /// `client_id` + `action` won't trigger notifiers twice.
+    pub fn with_notify_debounce_window(mut self, window: chrono::Duration) -> Self {
+        self.notify_debounce_window = window;
+        self
+    }
+
     /// Log a security event
     pub fn log_event(&mut self, entry: AuditLogEntry) {
         if !self.enabled {
             return;
         }
-        
+
         // Analyze for patterns
         let risk_adjustment = self.security_patterns.analyze_event(&entry);
         let mut adjusted_entry = entry;
-        adjusted_entry.risk_score += risk_adjustment;
-        
+        adjusted_entry.risk_score = adjusted_entry.risk_score.saturating_add(risk_adjustment);
+
         // Log immediately
         adjusted_entry.log();
-        
-        // Add to buffer for pattern analysis
-        self.buffer.push(adjusted_entry);
-        
-        // Maintain buffer size
-        if self.buffer.len() > self.max_buffer_size {
-            self.buffer.remove(0);
+
+        for sink in &self.sinks {
+            sink.emit(&adjusted_entry);
         }
+
+        self.notify_if_qualifying(&adjusted_entry);
+
+        self.store.append(adjusted_entry);
     }
-    
-    /// Log authentication attempt
-    pub fn log_authentication(&mut self, client_id: Option<String>, source_ip: Option<String>, success: bool, details: HashMap<String, String>) {
-        let entry = AuditLogEntry::new(
-            SecurityEventType::Authentication,
-            if success { EventSeverity::Info } else { EventSeverity::Medium },
-            if success { "authentication_success".to_string() } else { "authentication_failure".to_string() }
-        )
-        .with_result(if success { "success".to_string() } else { "failure".to_string() });
-        
-        let mut final_entry = entry;
-        if let Some(cid) = client_id {
-            final_entry = final_entry.with_client_id(cid);
+
+    /// Fires every registered `Notifier` for `Critical`/`High` severity
+    /// events or events whose risk score crosses `notify_risk_score_threshold`
+    /// - unless the same `client_id` + `action` pair already fired one within
+    /// `notify_debounce_window`, so a brute-force burst pages once rather
+    /// than hundreds of times.
+    fn notify_if_qualifying(&mut self, entry: &AuditLogEntry) {
+        if self.notifiers.is_empty() {
+            return;
         }
-        if let Some(ip) = source_ip {
-            final_entry = final_entry.with_source_ip(ip);
+
+        let qualifies = matches!(entry.severity, EventSeverity::Critical | EventSeverity::High)
+            || entry.risk_score >= self.notify_risk_score_threshold;
+        if !qualifies {
+            return;
         }
-        
-        for (key, value) in details {
-            final_entry = final_entry.with_detail(key, value);
+
+        let debounce_key = (entry.client_id.clone().unwrap_or_default(), entry.action.clone());
+        if let Some(last_fired) = self.last_notified.get(&debounce_key) {
+            if entry.timestamp.signed_duration_since(*last_fired) < self.notify_debounce_window {
+                return;
+            }
+        } else {
+            self.evict_oldest_notified_if_full();
+        }
+
+        self.last_notified.insert(debounce_key, entry.timestamp);
+        for notifier in &self.notifiers {
+            notifier.notify(entry);
         }
-        
-        self.log_event(final_entry);
     }
-    
+
+    /// Evicts the oldest-fired debounce entry once tracking would exceed
+    /// `max_notify_keys`, so a client rotating its id on every request can't
+    /// grow `last_notified` without bound.
+    fn evict_oldest_notified_if_full(&mut self) {
+        if self.last_notified.len() < self.max_notify_keys {
+            return;
+        }
+
+        if let Some(oldest_key) = self.last_notified
+            .iter()
+            .min_by_key(|(_, fired_at)| **fired_at)
+            .map(|(key, _)| key.clone())
+        {
+            self.last_notified.remove(&oldest_key);
+        }
+    }
+
+    /// Log authentication attempt
+    pub fn log_authentication(&mut self, client_id: Option<String>, source_ip: Option<String>, success: bool, details: HashMap<String, String>) {
+        self.log_event(authentication_entry(client_id, source_ip, success, details));
+    }
+
     /// Log rate limiting event
-    pub fn log_rate_limiting(&mut self, client_id: String, source_ip: Option<String>, limit_type: String, current_count: u32, limit: u32) {
-        let entry = AuditLogEntry::new(
-            SecurityEventType::RateLimiting,
-            EventSeverity::Medium,
-            "rate_limit_exceeded".to_string()
-        )
-        .with_client_id(client_id)
-        .with_result("blocked".to_string())
-        .with_detail("limit_type".to_string(), limit_type)
-        .with_detail("current_count".to_string(), current_count.to_string())
-        .with_detail("limit".to_string(), limit.to_string())
-        .with_risk_score(10);
-        
-        let final_entry = if let Some(ip) = source_ip {
-            entry.with_source_ip(ip)
-        } else {
-            entry
-        };
-        
-        self.log_event(final_entry);
+    pub fn log_rate_limiting(&mut self, client_id: String, source_ip: Option<String>, limit_type: String, current_count: u32, limit: u32, request_id: Option<String>) {
+        self.log_event(rate_limiting_entry(client_id, source_ip, limit_type, current_count, limit, request_id));
     }
-    
+
     /// Log input validation failure
-    pub fn log_validation_failure(&mut self, client_id: Option<String>, source_ip: Option<String>, field: String, error: String) {
-        let entry = AuditLogEntry::new(
-            SecurityEventType::InputValidation,
-            EventSeverity::Medium,
-            "input_validation_failed".to_string()
-        )
-        .with_result("rejected".to_string())
-        .with_detail("field".to_string(), field)
-        .with_detail("error".to_string(), error)
-        .with_risk_score(15);
-        
-        let mut final_entry = entry;
-        if let Some(cid) = client_id {
-            final_entry = final_entry.with_client_id(cid);
-        }
-        if let Some(ip) = source_ip {
-            final_entry = final_entry.with_source_ip(ip);
-        }
-        
-        self.log_event(final_entry);
+    pub fn log_validation_failure(&mut self, client_id: Option<String>, source_ip: Option<String>, field: String, error: String, request_id: Option<String>) {
+        self.log_event(validation_failure_entry(client_id, source_ip, field, error, request_id));
     }
-    
+
     /// Log suspicious activity
     pub fn log_suspicious_activity(&mut self, client_id: String, source_ip: Option<String>, activity_type: String, risk_score: u32, details: HashMap<String, String>) {
-        let severity = match risk_score {
-            0..=25 => EventSeverity::Low,
-            26..=50 => EventSeverity::Medium,
-            51..=75 => EventSeverity::High,
-            _ => EventSeverity::Critical,
-        };
-        
-        let entry = AuditLogEntry::new(
-            SecurityEventType::SuspiciousActivity,
-            severity,
-            format!("suspicious_activity_{}", activity_type)
-        )
-        .with_client_id(client_id)
-        .with_result("detected".to_string())
-        .with_risk_score(risk_score);
-        
-        let mut final_entry = if let Some(ip) = source_ip {
-            entry.with_source_ip(ip)
-        } else {
-            entry
-        };
-        
-        for (key, value) in details {
-            final_entry = final_entry.with_detail(key, value);
-        }
-        
-        self.log_event(final_entry);
+        self.log_event(suspicious_activity_entry(client_id, source_ip, activity_type, risk_score, details));
     }
-    
+
     /// Log data access event
-    pub fn log_data_access(&mut self, client_id: Option<String>, source_ip: Option<String>, method: String, resource: String, success: bool) {
-        let entry = AuditLogEntry::new(
-            SecurityEventType::DataAccess,
-            EventSeverity::Info,
-            "data_access".to_string()
-        )
-        .with_method(method)
-        .with_resource(resource)
-        .with_result(if success { "success".to_string() } else { "failure".to_string() });
-        
-        let mut final_entry = entry;
-        if let Some(cid) = client_id {
-            final_entry = final_entry.with_client_id(cid);
-        }
-        if let Some(ip) = source_ip {
-            final_entry = final_entry.with_source_ip(ip);
-        }
-        
-        self.log_event(final_entry);
+    pub fn log_data_access(&mut self, client_id: Option<String>, source_ip: Option<String>, method: String, resource: String, success: bool, request_id: Option<String>) {
+        self.log_event(data_access_entry(client_id, source_ip, method, resource, success, request_id));
     }
-    
+
     /// Get recent security events for analysis
-    pub fn get_recent_events(&self, limit: Option<usize>) -> Vec<&AuditLogEntry> {
-        let limit = limit.unwrap_or(100);
-        self.buffer.iter().rev().take(limit).collect()
+    pub fn get_recent_events(&self, limit: Option<usize>) -> Vec<AuditLogEntry> {
+        self.store.recent(limit.unwrap_or(100))
     }
-    
+
+    /// Forensic lookup over the full event history (not just the last 1000
+    /// live events) by client, event type, time range, and/or minimum risk
+    /// score - whatever the configured `AuditStore` actually retains.
+    pub fn query(&self, filter: &AuditQuery) -> Vec<AuditLogEntry> {
+        self.store.query(filter)
+    }
+
     /// Generate security summary
     pub fn generate_security_summary(&self) -> serde_json::Value {
+        let entries = self.store.query(&AuditQuery::default());
         let mut event_counts = HashMap::new();
         let mut severity_counts = HashMap::new();
         let mut total_risk_score = 0u32;
         let mut high_risk_events = 0;
-        
-        for entry in &self.buffer {
+
+        for entry in &entries {
             let event_type_key = format!("{:?}", entry.event_type);
             *event_counts.entry(event_type_key).or_insert(0) += 1;
-            
+
             let severity_key = format!("{:?}", entry.severity);
             *severity_counts.entry(severity_key).or_insert(0) += 1;
-            
-            total_risk_score += entry.risk_score;
-            
+
+            total_risk_score = total_risk_score.saturating_add(entry.risk_score);
+
             if entry.risk_score > 50 {
                 high_risk_events += 1;
             }
         }
-        
-        let avg_risk_score = if !self.buffer.is_empty() {
-            total_risk_score as f64 / self.buffer.len() as f64
+
+        let avg_risk_score = if !entries.is_empty() {
+            total_risk_score as f64 / entries.len() as f64
         } else {
             0.0
         };
-        
+
         serde_json::json!({
-            "total_events": self.buffer.len(),
+            "total_events": entries.len(),
             "event_types": event_counts,
             "severity_distribution": severity_counts,
             "average_risk_score": avg_risk_score,
@@ -393,16 +882,322 @@ impl SecurityAuditor {
     }
 }
 
-/// Security pattern detector for behavioral analysis
+/// Shared by `SecurityAuditor`'s own `log_*` methods and `AuditHandle`'s
+/// ring-buffer-backed equivalents, so the two never classify the same event
+/// differently.
+fn authentication_entry(client_id: Option<String>, source_ip: Option<String>, success: bool, details: HashMap<String, String>) -> AuditLogEntry {
+    let entry = AuditLogEntry::new(
+        SecurityEventType::Authentication,
+        if success { EventSeverity::Info } else { EventSeverity::Medium },
+        if success { "authentication_success".to_string() } else { "authentication_failure".to_string() }
+    )
+    .with_result(if success { "success".to_string() } else { "failure".to_string() });
+
+    let mut final_entry = entry;
+    if let Some(cid) = client_id {
+        final_entry = final_entry.with_client_id(cid);
+    }
+    if let Some(ip) = source_ip {
+        final_entry = final_entry.with_source_ip(ip);
+    }
+    for (key, value) in details {
+        final_entry = final_entry.with_detail(key, value);
+    }
+    final_entry
+}
+
+fn rate_limiting_entry(client_id: String, source_ip: Option<String>, limit_type: String, current_count: u32, limit: u32, request_id: Option<String>) -> AuditLogEntry {
+    let entry = AuditLogEntry::new(
+        SecurityEventType::RateLimiting,
+        EventSeverity::Medium,
+        "rate_limit_exceeded".to_string()
+    )
+    .with_client_id(client_id)
+    .with_result("blocked".to_string())
+    .with_detail("limit_type".to_string(), limit_type)
+    .with_detail("current_count".to_string(), current_count.to_string())
+    .with_detail("limit".to_string(), limit.to_string())
+    .with_risk_score(10);
+
+    let mut final_entry = if let Some(ip) = source_ip {
+        entry.with_source_ip(ip)
+    } else {
+        entry
+    };
+    if let Some(rid) = request_id {
+        final_entry = final_entry.with_request_id(rid);
+    }
+    final_entry
+}
+
+fn validation_failure_entry(client_id: Option<String>, source_ip: Option<String>, field: String, error: String, request_id: Option<String>) -> AuditLogEntry {
+    let entry = AuditLogEntry::new(
+        SecurityEventType::InputValidation,
+        EventSeverity::Medium,
+        "input_validation_failed".to_string()
+    )
+    .with_result("rejected".to_string())
+    .with_detail("field".to_string(), field)
+    .with_detail("error".to_string(), error)
+    .with_risk_score(15);
+
+    let mut final_entry = entry;
+    if let Some(cid) = client_id {
+        final_entry = final_entry.with_client_id(cid);
+    }
+    if let Some(ip) = source_ip {
+        final_entry = final_entry.with_source_ip(ip);
+    }
+    if let Some(rid) = request_id {
+        final_entry = final_entry.with_request_id(rid);
+    }
+    final_entry
+}
+
+fn suspicious_activity_entry(client_id: String, source_ip: Option<String>, activity_type: String, risk_score: u32, details: HashMap<String, String>) -> AuditLogEntry {
+    let severity = match risk_score {
+        0..=25 => EventSeverity::Low,
+        26..=50 => EventSeverity::Medium,
+        51..=75 => EventSeverity::High,
+        _ => EventSeverity::Critical,
+    };
+
+    let entry = AuditLogEntry::new(
+        SecurityEventType::SuspiciousActivity,
+        severity,
+        format!("suspicious_activity_{}", activity_type)
+    )
+    .with_client_id(client_id)
+    .with_result("detected".to_string())
+    .with_risk_score(risk_score);
+
+    let mut final_entry = if let Some(ip) = source_ip {
+        entry.with_source_ip(ip)
+    } else {
+        entry
+    };
+    for (key, value) in details {
+        final_entry = final_entry.with_detail(key, value);
+    }
+    final_entry
+}
+
+fn data_access_entry(client_id: Option<String>, source_ip: Option<String>, method: String, resource: String, success: bool, request_id: Option<String>) -> AuditLogEntry {
+    let entry = AuditLogEntry::new(
+        SecurityEventType::DataAccess,
+        EventSeverity::Info,
+        "data_access".to_string()
+    )
+    .with_method(method)
+    .with_resource(resource)
+    .with_result(if success { "success".to_string() } else { "failure".to_string() });
+
+    let mut final_entry = entry;
+    if let Some(cid) = client_id {
+        final_entry = final_entry.with_client_id(cid);
+    }
+    if let Some(ip) = source_ip {
+        final_entry = final_entry.with_source_ip(ip);
+    }
+    if let Some(rid) = request_id {
+        final_entry = final_entry.with_request_id(rid);
+    }
+    final_entry
+}
+
+/// Cheap, atomically-swappable read view of the consumer-owned
+/// `SecurityAuditor` state, refreshed after each drain pass so
+/// `handle_security_status` and `handle_security_audit` never wait on the
+/// background event-processing task.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditSnapshot {
+    pub recent_events: Vec<AuditLogEntry>,
+    pub summary: serde_json::Value,
+}
+
+impl Default for AuditSnapshot {
+    fn default() -> Self {
+        Self {
+            recent_events: Vec::new(),
+            summary: serde_json::json!({}),
+        }
+    }
+}
+
+/// Lock-free front door onto the audit pipeline. Request handlers hold this
+/// (behind an `Arc`) and call its `log_*` methods directly from the hot
+/// path - each one builds an `AuditLogEntry` and pushes it onto an SPSC ring
+/// buffer without ever awaiting. A dedicated background task owns the
+/// consumer side and the real `SecurityAuditor`, draining events, running
+/// them through pattern analysis, and publishing a fresh `AuditSnapshot`
+/// after each drain so readers never contend with the writer side.
+///
+/// The ring buffer itself only supports a single producer; since many
+/// concurrent request tasks share one `AuditHandle`, pushes are serialized
+/// through a `std::sync::Mutex` acquired with `try_lock` rather than an
+/// async mutex - a push that loses the race is counted as dropped instead
+/// of waiting, so the caller still never awaits.
+pub struct AuditHandle {
+    producer: StdMutex<rtrb::Producer<AuditLogEntry>>,
+    dropped: AtomicU64,
+    snapshot: ArcSwap<AuditSnapshot>,
+}
+
+/// Real-time notifier wiring for `AuditHandle::spawn`: the registered
+/// notifiers plus the thresholds deciding when they fire. `Default` disables
+/// notification entirely (an empty notifier list never fires regardless of
+/// threshold).
+pub struct NotifyOptions {
+    pub notifiers: Vec<Box<dyn Notifier>>,
+    pub risk_score_threshold: u32,
+    pub debounce_window: chrono::Duration,
+}
+
+impl Default for NotifyOptions {
+    fn default() -> Self {
+        Self {
+            notifiers: Vec::new(),
+            risk_score_threshold: DEFAULT_NOTIFY_RISK_SCORE_THRESHOLD,
+            debounce_window: chrono::Duration::minutes(5),
+        }
+    }
+}
+
+impl AuditHandle {
+    /// Spawns the background consumer task and returns the producer-side
+    /// handle. `capacity` bounds the ring buffer; once full (or if another
+    /// push is already in flight) new events are dropped and counted rather
+    /// than blocking the caller. `metrics`, when given, receives a
+    /// `security_event` counter broken down by `severity` as events drain -
+    /// this is the one place an entry's severity is already known, so callers
+    /// elsewhere don't need to duplicate that mapping.
+    pub fn spawn(enabled: bool, sinks: Vec<Box<dyn AuditSink>>, notify: NotifyOptions, capacity: usize, metrics: Option<Arc<MetricsCollector>>) -> Arc<Self> {
+        let (producer, mut consumer) = RingBuffer::<AuditLogEntry>::new(capacity);
+
+        let handle = Arc::new(Self {
+            producer: StdMutex::new(producer),
+            dropped: AtomicU64::new(0),
+            snapshot: ArcSwap::from_pointee(AuditSnapshot::default()),
+        });
+
+        let consumer_handle = handle.clone();
+        tokio::spawn(async move {
+            let mut auditor = SecurityAuditor::new(enabled)
+                .with_notify_risk_score_threshold(notify.risk_score_threshold)
+                .with_notify_debounce_window(notify.debounce_window);
+            for sink in sinks {
+                auditor = auditor.with_sink(sink);
+            }
+            for notifier in notify.notifiers {
+                auditor = auditor.with_notifier(notifier);
+            }
+
+            loop {
+                let mut drained = false;
+                while let Ok(entry) = consumer.pop() {
+                    if let Some(metrics) = &metrics {
+                        let labels = MetricLabels::new("security_event").with("severity", format!("{:?}", entry.severity));
+                        metrics.increment_request_count(labels).await;
+                    }
+                    auditor.log_event(entry);
+                    drained = true;
+                }
+
+                if drained {
+                    let recent_events = auditor.get_recent_events(Some(100));
+                    let mut summary = auditor.generate_security_summary();
+                    summary["dropped_events"] = serde_json::json!(consumer_handle.dropped_count());
+                    consumer_handle.snapshot.store(Arc::new(AuditSnapshot { recent_events, summary }));
+                }
+
+                tokio::time::sleep(Duration::from_millis(25)).await;
+            }
+        });
+
+        handle
+    }
+
+    /// Non-blocking push onto the ring buffer. Dropped (and counted) rather
+    /// than retried when the buffer is full or a concurrent push is already
+    /// holding the producer.
+    fn record(&self, entry: AuditLogEntry) {
+        let pushed = self.producer
+            .try_lock()
+            .map(|mut producer| producer.push(entry).is_ok())
+            .unwrap_or(false);
+
+        if !pushed {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            warn!("Audit ring buffer full or contended: dropping event");
+        }
+    }
+
+    /// Total events dropped so far because the ring buffer was full or
+    /// contended - surfaced in `AuditSnapshot::summary` as `dropped_events`.
+    pub fn dropped_count(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Cheap atomic read of the latest processed state; never blocks on the
+    /// consumer task.
+    pub fn snapshot(&self) -> Arc<AuditSnapshot> {
+        self.snapshot.load_full()
+    }
+
+    pub fn log_authentication(&self, client_id: Option<String>, source_ip: Option<String>, success: bool, details: HashMap<String, String>) {
+        self.record(authentication_entry(client_id, source_ip, success, details));
+    }
+
+    pub fn log_rate_limiting(&self, client_id: String, source_ip: Option<String>, limit_type: String, current_count: u32, limit: u32, request_id: Option<String>) {
+        self.record(rate_limiting_entry(client_id, source_ip, limit_type, current_count, limit, request_id));
+    }
+
+    pub fn log_validation_failure(&self, client_id: Option<String>, source_ip: Option<String>, field: String, error: String, request_id: Option<String>) {
+        self.record(validation_failure_entry(client_id, source_ip, field, error, request_id));
+    }
+
+    pub fn log_suspicious_activity(&self, client_id: String, source_ip: Option<String>, activity_type: String, risk_score: u32, details: HashMap<String, String>) {
+        self.record(suspicious_activity_entry(client_id, source_ip, activity_type, risk_score, details));
+    }
+
+    pub fn log_data_access(&self, client_id: Option<String>, source_ip: Option<String>, method: String, resource: String, success: bool, request_id: Option<String>) {
+        self.record(data_access_entry(client_id, source_ip, method, resource, success, request_id));
+    }
+}
+
+/// Caps `SecurityPatternDetector::client_patterns` so a client that rotates
+/// its id every request can't grow the map without bound; the
+/// least-recently-active entry is evicted once this is reached.
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+/// How quickly a client's failed/suspicious score ages out absent new
+/// events - at one half-life with no activity, the score has halved.
+const DEFAULT_HALF_LIFE_SECS: f64 = 300.0;
+
+const DEFAULT_SEVERE_THRESHOLD: f64 = 5.0;
+const DEFAULT_ELEVATED_THRESHOLD: f64 = 3.0;
+const DEFAULT_MILD_THRESHOLD: f64 = 1.0;
+
+/// Security pattern detector for behavioral analysis.
+///
+/// Per-client failure/suspicion counts decay exponentially with
+/// `half_life_secs` instead of accumulating for the life of the process, so
+/// a burst of failures ages out rather than permanently depressing a
+/// client's score.
 struct SecurityPatternDetector {
     client_patterns: HashMap<String, ClientPattern>,
+    max_clients: usize,
+    half_life_secs: f64,
+    severe_threshold: f64,
+    elevated_threshold: f64,
+    mild_threshold: f64,
 }
 
 #[derive(Debug)]
 struct ClientPattern {
     request_count: u32,
-    failed_attempts: u32,
-    suspicious_activities: u32,
+    failed_score: f64,
+    suspicious_score: f64,
     last_activity: chrono::DateTime<chrono::Utc>,
     pattern_score: u32,
 }
@@ -411,68 +1206,117 @@ impl SecurityPatternDetector {
     fn new() -> Self {
         Self {
             client_patterns: HashMap::new(),
+            max_clients: MAX_TRACKED_CLIENTS,
+            half_life_secs: DEFAULT_HALF_LIFE_SECS,
+            severe_threshold: DEFAULT_SEVERE_THRESHOLD,
+            elevated_threshold: DEFAULT_ELEVATED_THRESHOLD,
+            mild_threshold: DEFAULT_MILD_THRESHOLD,
         }
     }
-    
+
+    /// Decays `score` for the time elapsed since `last_activity`, assuming
+    /// the client has been silent since then.
+    fn decay(&self, score: f64, last_activity: chrono::DateTime<chrono::Utc>, as_of: chrono::DateTime<chrono::Utc>) -> f64 {
+        let elapsed_secs = as_of.signed_duration_since(last_activity).num_milliseconds() as f64 / 1000.0;
+        score * 0.5_f64.powf(elapsed_secs.max(0.0) / self.half_life_secs)
+    }
+
+    fn risk_adjustment_for(&self, decayed_score: f64) -> u32 {
+        if decayed_score > self.severe_threshold {
+            20
+        } else if decayed_score > self.elevated_threshold {
+            10
+        } else if decayed_score > self.mild_threshold {
+            5
+        } else {
+            0
+        }
+    }
+
     fn analyze_event(&mut self, entry: &AuditLogEntry) -> u32 {
         if let Some(client_id) = &entry.client_id {
+            if !self.client_patterns.contains_key(client_id) {
+                self.evict_least_recently_active_if_full();
+            }
+
             let pattern = self.client_patterns
                 .entry(client_id.clone())
                 .or_insert_with(|| ClientPattern {
                     request_count: 0,
-                    failed_attempts: 0,
-                    suspicious_activities: 0,
-                    last_activity: chrono::Utc::now(),
+                    failed_score: 0.0,
+                    suspicious_score: 0.0,
+                    last_activity: entry.timestamp,
                     pattern_score: 0,
                 });
-            
-            pattern.request_count += 1;
+
+            pattern.request_count = pattern.request_count.saturating_add(1);
+
+            pattern.failed_score = self.decay(pattern.failed_score, pattern.last_activity, entry.timestamp);
+            pattern.suspicious_score = self.decay(pattern.suspicious_score, pattern.last_activity, entry.timestamp);
             pattern.last_activity = entry.timestamp;
-            
+
             match entry.event_type {
                 SecurityEventType::Authentication if entry.result == "failure" => {
-                    pattern.failed_attempts += 1;
+                    pattern.failed_score += 1.0;
                 }
                 SecurityEventType::SuspiciousActivity => {
-                    pattern.suspicious_activities += 1;
+                    pattern.suspicious_score += 1.0;
                 }
                 SecurityEventType::InputValidation if entry.result == "rejected" => {
-                    pattern.suspicious_activities += 1;
+                    pattern.suspicious_score += 1.0;
                 }
                 _ => {}
             }
-            
-            // Calculate pattern-based risk adjustment
-            let risk_adjustment = match (pattern.failed_attempts, pattern.suspicious_activities) {
-                (f, s) if f > 5 || s > 3 => 20,
-                (f, s) if f > 3 || s > 1 => 10,
-                (f, s) if f > 1 || s > 0 => 5,
-                _ => 0,
-            };
-            
+
+            // Calculate pattern-based risk adjustment from whichever decayed
+            // score is currently worse for this client.
+            let risk_adjustment = self.risk_adjustment_for(pattern.failed_score.max(pattern.suspicious_score));
+
             pattern.pattern_score = risk_adjustment;
             risk_adjustment
         } else {
             0
         }
     }
-    
+
+    /// Evicts the least-recently-active client once tracking would exceed
+    /// `max_clients`, so a client rotating its id on every request can't grow
+    /// this map without bound.
+    fn evict_least_recently_active_if_full(&mut self) {
+        if self.client_patterns.len() < self.max_clients {
+            return;
+        }
+
+        if let Some(oldest_client) = self.client_patterns
+            .iter()
+            .min_by_key(|(_, pattern)| pattern.last_activity)
+            .map(|(client_id, _)| client_id.clone())
+        {
+            self.client_patterns.remove(&oldest_client);
+        }
+    }
+
     fn get_pattern_summary(&self) -> serde_json::Value {
+        let now = chrono::Utc::now();
+
         let high_risk_clients = self.client_patterns
-            .iter()
-            .filter(|(_, pattern)| pattern.pattern_score > 15)
+            .values()
+            .filter(|pattern| {
+                let decayed = self.decay(pattern.failed_score.max(pattern.suspicious_score), pattern.last_activity, now);
+                decayed > self.severe_threshold
+            })
             .count();
-        
-        let total_failed_attempts: u32 = self.client_patterns
+
+        let total_failed_attempts: f64 = self.client_patterns
             .values()
-            .map(|p| p.failed_attempts)
+            .map(|p| self.decay(p.failed_score, p.last_activity, now))
             .sum();
-        
-        let total_suspicious: u32 = self.client_patterns
+
+        let total_suspicious: f64 = self.client_patterns
             .values()
-            .map(|p| p.suspicious_activities)
+            .map(|p| self.decay(p.suspicious_score, p.last_activity, now))
             .sum();
-        
+
         serde_json::json!({
             "total_clients_tracked": self.client_patterns.len(),
             "high_risk_clients": high_risk_clients,
@@ -529,4 +1373,136 @@ mod tests {
         let summary = auditor.generate_security_summary();
         assert!(summary["total_events"].as_u64().unwrap() > 0);
     }
+
+    #[test]
+    fn in_memory_store_evicts_oldest_past_max_size() {
+        let mut store = InMemoryAuditStore::new(2);
+        for i in 0..3 {
+            store.append(AuditLogEntry::new(SecurityEventType::DataAccess, EventSeverity::Info, format!("action_{}", i)));
+        }
+        assert_eq!(store.len(), 2);
+        assert_eq!(store.recent(10)[1].action, "action_1");
+    }
+
+    #[test]
+    fn audit_query_filters_by_client_and_min_risk_score() {
+        let mut store = InMemoryAuditStore::new(10);
+        store.append(
+            AuditLogEntry::new(SecurityEventType::SuspiciousActivity, EventSeverity::High, "a".to_string())
+                .with_client_id("alice".to_string())
+                .with_risk_score(80),
+        );
+        store.append(
+            AuditLogEntry::new(SecurityEventType::SuspiciousActivity, EventSeverity::Low, "b".to_string())
+                .with_client_id("bob".to_string())
+                .with_risk_score(10),
+        );
+
+        let results = store.query(&AuditQuery { client_id: Some("alice".to_string()), min_risk_score: Some(50), ..Default::default() });
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].action, "a");
+    }
+
+    #[test]
+    fn pattern_detector_evicts_least_recently_active_client_once_full() {
+        let mut detector = SecurityPatternDetector::new();
+        detector.max_clients = 2;
+
+        for client in ["client_a", "client_b", "client_c"] {
+            let entry = AuditLogEntry::new(SecurityEventType::DataAccess, EventSeverity::Info, "access".to_string())
+                .with_client_id(client.to_string());
+            detector.analyze_event(&entry);
+        }
+
+        assert_eq!(detector.client_patterns.len(), 2);
+        assert!(!detector.client_patterns.contains_key("client_a"));
+        assert!(detector.client_patterns.contains_key("client_c"));
+    }
+
+    #[test]
+    fn pattern_detector_score_decays_between_events() {
+        let mut detector = SecurityPatternDetector::new();
+        detector.half_life_secs = 60.0;
+
+        let base_time = chrono::Utc::now();
+        let mut failure = AuditLogEntry::new(SecurityEventType::Authentication, EventSeverity::Medium, "login".to_string())
+            .with_client_id("repeat_offender".to_string());
+        failure.result = "failure".to_string();
+
+        for i in 0..6 {
+            failure.timestamp = base_time + chrono::Duration::seconds(i);
+            detector.analyze_event(&failure);
+        }
+        let fresh_score = detector.client_patterns["repeat_offender"].failed_score;
+        assert!(fresh_score > 5.0, "expected decayed score above the severe threshold, got {}", fresh_score);
+
+        // Same client, but the next event arrives ten half-lives later - the
+        // prior failures should have aged almost entirely out.
+        failure.timestamp = base_time + chrono::Duration::seconds(600);
+        let risk_adjustment = detector.analyze_event(&failure);
+        assert_eq!(risk_adjustment, 0);
+    }
+
+    #[test]
+    fn notifier_fires_once_per_debounce_window_for_same_client_and_action() {
+        let fired = Arc::new(StdMutex::new(0u32));
+        let fired_clone = fired.clone();
+        let mut auditor = SecurityAuditor::new(true)
+            .with_notifier(Box::new(CallbackNotifier::new(move |_entry: &AuditLogEntry| {
+                *fired_clone.lock().unwrap() += 1;
+            })));
+
+        for _ in 0..3 {
+            auditor.log_suspicious_activity(
+                "attacker".to_string(),
+                None,
+                "brute_force".to_string(),
+                90,
+                HashMap::new(),
+            );
+        }
+
+        assert_eq!(*fired.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn notifier_does_not_fire_below_severity_and_risk_score_threshold() {
+        let fired = Arc::new(StdMutex::new(0u32));
+        let fired_clone = fired.clone();
+        let mut auditor = SecurityAuditor::new(true)
+            .with_notifier(Box::new(CallbackNotifier::new(move |_entry: &AuditLogEntry| {
+                *fired_clone.lock().unwrap() += 1;
+            })));
+
+        auditor.log_data_access(
+            Some("client".to_string()),
+            None,
+            "tools/call".to_string(),
+            "analyze_files".to_string(),
+            true,
+            None,
+        );
+
+        assert_eq!(*fired.lock().unwrap(), 0);
+    }
+
+    #[test]
+    fn last_notified_evicts_oldest_debounce_entry_once_full() {
+        let mut auditor = SecurityAuditor::new(true)
+            .with_notifier(Box::new(CallbackNotifier::new(|_entry: &AuditLogEntry| {})));
+        auditor.max_notify_keys = 2;
+
+        for client in ["client_a", "client_b", "client_c"] {
+            auditor.log_suspicious_activity(
+                client.to_string(),
+                None,
+                "brute_force".to_string(),
+                90,
+                HashMap::new(),
+            );
+        }
+
+        assert_eq!(auditor.last_notified.len(), 2);
+        assert!(!auditor.last_notified.contains_key(&("client_a".to_string(), "suspicious_activity_brute_force".to_string())));
+    }
 }
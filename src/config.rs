@@ -1,9 +1,84 @@
 use anyhow::Result;
+use notify::Watcher;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::watch;
 use tracing::{info, debug, warn};
 
+/// How long `Config::watch` waits after the first file event in a burst
+/// before reloading, so an editor's save-as-several-writes triggers one
+/// reload instead of one per write.
+const RELOAD_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Serialization formats a `Config` file can be read from (by
+/// `Config::load_from_file` / `ConfigBuilder`) or dumped to (via
+/// `Config::to_format`), selected by file extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl Format {
+    /// Maps a file extension (without the leading dot, case-insensitive) to
+    /// the format that reads/writes it. `None` for anything else, so the
+    /// caller can name the unsupported extension in its own error.
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext.to_ascii_lowercase().as_str() {
+            "json" => Some(Format::Json),
+            "yaml" | "yml" => Some(Format::Yaml),
+            "toml" => Some(Format::Toml),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Format {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Format::Json => "json",
+            Format::Yaml => "yaml",
+            Format::Toml => "toml",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+fn format_for_path(path: &Path) -> Result<Format> {
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    Format::from_extension(ext).ok_or_else(|| {
+        anyhow::anyhow!(
+            "Unsupported config file extension \"{}\" (expected json, yaml, yml, or toml): {:?}",
+            ext,
+            path
+        )
+    })
+}
+
+/// Parses `content` as `format` directly into a `Config`. Shared by
+/// `Config::load_from_file`, which only ever needs a whole, valid config.
+fn parse_config_str(format: Format, content: &str) -> Result<Config> {
+    match format {
+        Format::Json => Ok(serde_json::from_str(content)?),
+        Format::Yaml => Ok(serde_yaml::from_str(content)?),
+        Format::Toml => Ok(toml::from_str(content)?),
+    }
+}
+
+/// Parses `content` as `format` into a generic JSON value tree rather than
+/// a whole `Config`, so `ConfigBuilder` can deep-merge a partial YAML/TOML
+/// file the same way it already merges partial JSON.
+fn parse_config_value(format: Format, content: &str) -> Result<serde_json::Value> {
+    match format {
+        Format::Json => Ok(serde_json::from_str(content)?),
+        Format::Yaml => Ok(serde_yaml::from_str(content)?),
+        Format::Toml => Ok(toml::from_str(content)?),
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub server: ServerConfig,
@@ -13,17 +88,47 @@ pub struct Config {
     pub metrics: MetricsConfig,
     pub performance: PerformanceConfig,
     pub cache: CacheConfig,
+    pub compression: CompressionConfig,
     pub circuit_breaker: CircuitBreakerConfig,
+    pub audit_sink: AuditSinkConfig,
+    pub notify: NotifyConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerConfig {
     pub host: String,
+    /// `0` means "bind an OS-assigned ephemeral port" - `Config::reserve_listener`
+    /// writes the port it actually bound back into this field, so callers that
+    /// started with `0` (tests, dynamic deployments) can read the real port
+    /// afterward.
     pub port: u16,
     pub workers: usize,
     pub timeout_seconds: u64,
     pub max_connections: usize,
     pub cors_enabled: bool,
+    /// Selects how `DeepSeekMcpHandler` is driven: `"stdio"` (default, one process
+    /// per client) or `"http"` (a shared `POST /mcp` + `GET /mcp/sse` service).
+    pub transport: String,
+    pub tcp: TcpConfig,
+}
+
+/// Low-level socket tuning for the `"http"` transport's listener, applied via
+/// `socket2` before the `TcpListener` axum serves from is built - plain
+/// `TcpListener::bind` exposes none of these. Ignored by the `"stdio"`
+/// transport, which never opens a listening socket.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TcpConfig {
+    /// `TCP_FASTOPEN` queue length; `None` leaves fast open disabled.
+    pub fast_open_backlog: Option<u32>,
+    pub keepalive_enabled: bool,
+    pub keepalive_idle_seconds: u64,
+    pub keepalive_interval_seconds: u64,
+    pub keepalive_probes: u32,
+    pub nodelay: bool,
+    /// When set, the HTTP transport periodically samples `TCP_INFO` (rtt,
+    /// retransmits) off each accepted connection and reports it through
+    /// `MetricsCollector`.
+    pub collect_tcp_info: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -70,6 +175,32 @@ pub struct MetricsConfig {
     pub port: u16,
     pub path: String,
     pub collect_detailed: bool,
+    pub otlp: OtlpConfig,
+    pub consumption: ConsumptionConfig,
+}
+
+/// Push-based OTLP export, as an alternative to the pull-only Prometheus text
+/// endpoint for environments with no inbound scrape access.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OtlpConfig {
+    pub enabled: bool,
+    pub endpoint: String,
+    pub interval_seconds: u64,
+    pub service_name: String,
+    pub service_version: String,
+}
+
+/// Periodic usage-delta upload to a billing/telemetry endpoint, independent of
+/// OTLP/Prometheus export. Uploads are chunked and idempotency-keyed so a
+/// crash mid-upload can safely resume without double-counting usage.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsumptionConfig {
+    pub enabled: bool,
+    pub endpoint: String,
+    pub interval_seconds: u64,
+    pub node_id: String,
+    pub chunk_size: usize,
+    pub cache_path: PathBuf,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -81,6 +212,14 @@ pub struct PerformanceConfig {
     pub file_processing_concurrency: usize,
     pub enable_request_deduplication: bool,
     pub enable_streaming: bool,
+    /// Worker threads for the explicitly-constructed `tokio::runtime::Runtime`
+    /// built in `main`, replacing `#[tokio::main]`'s opaque default. Defaults
+    /// to the host's available parallelism.
+    pub worker_threads: usize,
+    /// How long `main` waits for in-flight requests and the cache-cleanup task
+    /// to drain once a shutdown signal (EOF, `ctrl_c`, `SIGTERM`) arrives,
+    /// before giving up and exiting anyway.
+    pub graceful_shutdown_timeout_ms: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -93,6 +232,54 @@ pub struct CacheConfig {
     pub invalidation_strategy: String,
 }
 
+/// Wire compression for the HTTP transport's responses, negotiated per-request
+/// against the client's `Accept-Encoding`. Independent of `CacheConfig`: the
+/// cache (`deepseek.rs`'s `response_cache`) stores the uncompressed upstream
+/// body, and compression is applied once, at the outer HTTP response edge, so
+/// neither feature can corrupt what the other sees.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompressionConfig {
+    pub enabled: bool,
+    /// Subset of `"gzip"`, `"deflate"`, `"zstd"`, in no particular order -
+    /// negotiation still follows the client's own `Accept-Encoding` quality
+    /// values, this only restricts which of them the server is willing to use.
+    pub algorithms: Vec<String>,
+    /// Responses smaller than this are sent uncompressed; compressing a tiny
+    /// body usually costs more bytes than it saves. Bodies of unknown length
+    /// (e.g. the SSE stream) are never skipped by this check.
+    pub min_size_bytes: usize,
+    /// Coarse 0-9 compression level, mapped down to the handful of tiers the
+    /// underlying encoders actually expose (fastest/default/best).
+    pub level: u32,
+}
+
+/// External destination security events are additionally streamed to,
+/// alongside the always-on in-process buffer `SecurityAuditor` already keeps.
+/// `endpoint` is opaque to this crate - interpreting it (Kafka broker list,
+/// HTTP collector URL, etc.) is left to whatever forwarding closure wires the
+/// sink up, matching how `OtlpConfig`/`ConsumptionConfig` only describe where
+/// to send data, not how.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditSinkConfig {
+    pub enabled: bool,
+    pub endpoint: String,
+    pub buffer_size: usize,
+    pub syslog_enabled: bool,
+    pub syslog_address: String,
+    pub file_path: Option<String>,
+    pub file_max_bytes: u64,
+}
+
+/// Real-time alerting for Critical/High severity (or high risk_score) audit
+/// events, separate from `AuditSinkConfig`'s passive event persistence.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotifyConfig {
+    pub webhook_enabled: bool,
+    pub webhook_url: String,
+    pub risk_score_threshold: u32,
+    pub debounce_seconds: i64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CircuitBreakerConfig {
     pub enabled: bool,
@@ -102,6 +289,35 @@ pub struct CircuitBreakerConfig {
     pub timeout_duration_ms: u64,
 }
 
+/// Whether a `ValidationIssue` fails `Config::validate` or is purely
+/// informational.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationSeverity {
+    Error,
+    Warning,
+}
+
+/// A single problem found by `Config::validate_all`, with enough context
+/// (field, message, severity) for a caller to print a full report - or for
+/// the hot-reload watcher to log every issue when rejecting a bad file,
+/// rather than just the first one `validate()` would have stopped at.
+#[derive(Debug, Clone)]
+pub struct ValidationIssue {
+    pub field: String,
+    pub message: String,
+    pub severity: ValidationSeverity,
+}
+
+impl std::fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let severity = match self.severity {
+            ValidationSeverity::Error => "error",
+            ValidationSeverity::Warning => "warning",
+        };
+        write!(f, "[{}] {}: {}", severity, self.field, self.message)
+    }
+}
+
 impl Config {
     pub fn load(config_path: Option<PathBuf>, env: &str) -> Result<Self> {
         // Load environment variables
@@ -109,238 +325,229 @@ impl Config {
             debug!("No .env file found, using environment variables only");
         }
 
-        let config = match config_path {
+        let mut builder = ConfigBuilder::new(env);
+        match &config_path {
             Some(path) => {
                 info!("Loading configuration from: {:?}", path);
-                Self::load_from_file(&path)?
+                builder = builder.with_file(path.clone());
             }
-            None => {
-                info!("Loading configuration from environment");
-                Self::load_from_env(env)?
-            }
-        };
+            None => info!("Loading configuration from environment"),
+        }
 
-        // Validate configuration
-        config.validate()?;
-        
-        Ok(config)
+        builder.build().map_err(|errors| {
+            let joined = errors
+                .into_iter()
+                .map(|e| format!("{} ({:?}): {}", e.field_path, e.layer, e.message))
+                .collect::<Vec<_>>()
+                .join("; ");
+            anyhow::anyhow!(joined)
+        })
     }
 
     fn load_from_file(path: &PathBuf) -> Result<Self> {
         let content = std::fs::read_to_string(path)?;
-        let config: Config = serde_json::from_str(&content)?;
-        Ok(config)
+        let format = format_for_path(path)?;
+        parse_config_str(format, &content)
     }
 
-    fn load_from_env(env: &str) -> Result<Self> {
-        let deepseek_api_key = std::env::var("DEEPSEEK_API_KEY")
-            .unwrap_or_else(|_| String::new());
-
-        let config = Config {
-            server: ServerConfig {
-                host: std::env::var("HOST").unwrap_or_else(|_| "127.0.0.1".to_string()),
-                port: std::env::var("PORT")
-                    .unwrap_or_else(|_| "8080".to_string())
-                    .parse()
-                    .unwrap_or(8080),
-                workers: std::env::var("WORKERS")
-                    .unwrap_or_else(|_| "4".to_string())
-                    .parse()
-                    .unwrap_or(4),
-                timeout_seconds: std::env::var("TIMEOUT_SECONDS")
-                    .unwrap_or_else(|_| "30".to_string())
-                    .parse()
-                    .unwrap_or(30),
-                max_connections: std::env::var("MAX_CONNECTIONS")
-                    .unwrap_or_else(|_| "1000".to_string())
-                    .parse()
-                    .unwrap_or(1000),
-                cors_enabled: env != "production",
-            },
-            deepseek: DeepSeekConfig {
-                api_key: deepseek_api_key.clone(),
-                base_url: std::env::var("DEEPSEEK_BASE_URL")
-                    .unwrap_or_else(|_| "https://api.deepseek.com".to_string()),
-                model: std::env::var("DEEPSEEK_MODEL")
-                    .unwrap_or_else(|_| "deepseek-chat".to_string()),
-                max_tokens: std::env::var("DEEPSEEK_MAX_TOKENS")
-                    .unwrap_or_else(|_| "4096".to_string())
-                    .parse()
-                    .unwrap_or(4096),
-                temperature: std::env::var("DEEPSEEK_TEMPERATURE")
-                    .unwrap_or_else(|_| "0.7".to_string())
-                    .parse()
-                    .unwrap_or(0.7),
-                timeout_seconds: std::env::var("DEEPSEEK_TIMEOUT_SECONDS")
-                    .unwrap_or_else(|_| "60".to_string())
-                    .parse()
-                    .unwrap_or(60),
-                retry_attempts: std::env::var("DEEPSEEK_RETRY_ATTEMPTS")
-                    .unwrap_or_else(|_| "3".to_string())
-                    .parse()
-                    .unwrap_or(3),
-                rate_limit_per_minute: std::env::var("DEEPSEEK_RATE_LIMIT_PER_MINUTE")
-                    .unwrap_or_else(|_| "60".to_string())
-                    .parse()
-                    .unwrap_or(60),
-            },
-            mcp: McpConfig {
-                protocol_version: "2024-11-05".to_string(),
-                capabilities: McpCapabilities {
-                    tools: true,
-                    resources: true,
-                    prompts: true,
-                    sampling: true,
-                },
-                tools: vec![
-                    "search".to_string(),
-                    "analyze".to_string(),
-                    "generate".to_string(),
-                ],
-                resources: vec![
-                    "files".to_string(),
-                    "projects".to_string(),
-                ],
-            },
-            logging: LoggingConfig {
-                level: if env == "production" { "info".to_string() } else { "debug".to_string() },
-                format: "json".to_string(),
-                file: if env == "production" {
-                    Some(PathBuf::from("/var/log/deepseek-mcp-bridge.log"))
-                } else {
-                    None
-                },
-                rotation: true,
-                max_size_mb: 100,
-                max_files: 10,
-            },
-            metrics: MetricsConfig {
-                enabled: env == "production",
-                port: 9090,
-                path: "/metrics".to_string(),
-                collect_detailed: env != "production",
-            },
-            performance: PerformanceConfig {
-                connection_pool_size: std::env::var("CONNECTION_POOL_SIZE")
-                    .unwrap_or_else(|_| "10".to_string())
-                    .parse()
-                    .unwrap_or(10),
-                max_concurrent_requests: std::env::var("MAX_CONCURRENT_REQUESTS")
-                    .unwrap_or_else(|_| "100".to_string())
-                    .parse()
-                    .unwrap_or(100),
-                request_timeout_ms: std::env::var("REQUEST_TIMEOUT_MS")
-                    .unwrap_or_else(|_| "30000".to_string())
-                    .parse()
-                    .unwrap_or(30000),
-                routing_timeout_ms: std::env::var("ROUTING_TIMEOUT_MS")
-                    .unwrap_or_else(|_| "100".to_string())
-                    .parse()
-                    .unwrap_or(100),
-                file_processing_concurrency: std::env::var("FILE_PROCESSING_CONCURRENCY")
-                    .unwrap_or_else(|_| "8".to_string())
-                    .parse()
-                    .unwrap_or(8),
-                enable_request_deduplication: std::env::var("ENABLE_REQUEST_DEDUPLICATION")
-                    .unwrap_or_else(|_| "true".to_string())
-                    .parse()
-                    .unwrap_or(true),
-                enable_streaming: std::env::var("ENABLE_STREAMING")
-                    .unwrap_or_else(|_| "true".to_string())
-                    .parse()
-                    .unwrap_or(true),
-            },
-            cache: CacheConfig {
-                enabled: std::env::var("CACHE_ENABLED")
-                    .unwrap_or_else(|_| "true".to_string())
-                    .parse()
-                    .unwrap_or(true),
-                ttl_seconds: std::env::var("CACHE_TTL_SECONDS")
-                    .unwrap_or_else(|_| "300".to_string())
-                    .parse()
-                    .unwrap_or(300),
-                max_entries: std::env::var("CACHE_MAX_ENTRIES")
-                    .unwrap_or_else(|_| "1000".to_string())
-                    .parse()
-                    .unwrap_or(1000),
-                cache_response_bodies: std::env::var("CACHE_RESPONSE_BODIES")
-                    .unwrap_or_else(|_| "true".to_string())
-                    .parse()
-                    .unwrap_or(true),
-                cache_file_contents: std::env::var("CACHE_FILE_CONTENTS")
-                    .unwrap_or_else(|_| "true".to_string())
-                    .parse()
-                    .unwrap_or(true),
-                invalidation_strategy: std::env::var("CACHE_INVALIDATION_STRATEGY")
-                    .unwrap_or_else(|_| "ttl".to_string()),
-            },
-            circuit_breaker: CircuitBreakerConfig {
-                enabled: std::env::var("CIRCUIT_BREAKER_ENABLED")
-                    .unwrap_or_else(|_| "true".to_string())
-                    .parse()
-                    .unwrap_or(true),
-                failure_threshold: std::env::var("CIRCUIT_BREAKER_FAILURE_THRESHOLD")
-                    .unwrap_or_else(|_| "5".to_string())
-                    .parse()
-                    .unwrap_or(5),
-                recovery_timeout_seconds: std::env::var("CIRCUIT_BREAKER_RECOVERY_TIMEOUT")
-                    .unwrap_or_else(|_| "60".to_string())
-                    .parse()
-                    .unwrap_or(60),
-                half_open_max_calls: std::env::var("CIRCUIT_BREAKER_HALF_OPEN_MAX_CALLS")
-                    .unwrap_or_else(|_| "3".to_string())
-                    .parse()
-                    .unwrap_or(3),
-                timeout_duration_ms: std::env::var("CIRCUIT_BREAKER_TIMEOUT_MS")
-                    .unwrap_or_else(|_| "5000".to_string())
-                    .parse()
-                    .unwrap_or(5000),
-            },
-        };
-
-        Ok(config)
+    /// Serializes the effective, merged config back out as `format`, so the
+    /// bridge can dump what it's actually running with for debugging in
+    /// whichever of JSON/YAML/TOML the operator wants to diff against their
+    /// source file.
+    pub fn to_format(&self, format: Format) -> Result<String> {
+        match format {
+            Format::Json => Ok(serde_json::to_string_pretty(self)?),
+            Format::Yaml => Ok(serde_yaml::to_string(self)?),
+            Format::Toml => Ok(toml::to_string_pretty(self)?),
+        }
     }
 
+    /// Thin wrapper over `validate_all`: errors as soon as any hard issue
+    /// exists, folding every such issue into a single `anyhow::Error` so
+    /// existing `config.validate()?` call sites don't need to change.
+    /// Prefer `validate_all` when you want the full report (warnings
+    /// included) rather than a single pass/fail result.
     pub fn validate(&self) -> Result<()> {
-        if self.deepseek.api_key.is_empty() {
-            return Err(anyhow::anyhow!("DeepSeek API key is required"));
+        let hard_issues: Vec<String> = self
+            .validate_all()
+            .into_iter()
+            .filter(|issue| issue.severity == ValidationSeverity::Error)
+            .map(|issue| issue.to_string())
+            .collect();
+
+        if hard_issues.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!(hard_issues.join("; ")))
         }
+    }
+
+    /// Binds `server.host:server.port` immediately, so a misconfigured or
+    /// already-occupied port is reported as a precise startup error instead
+    /// of surfacing deep inside the server bring-up path. `server.port == 0`
+    /// is an explicit "pick an ephemeral port" mode: the OS-assigned port is
+    /// written back into `self.server.port` so callers (tests, dynamic
+    /// deployments) can read the real port afterward.
+    ///
+    /// Returns a plain `std::net::TcpListener` rather than a tokio one since
+    /// this runs before the async runtime is up; callers convert it with
+    /// `tokio::net::TcpListener::from_std` once they're ready to serve.
+    pub fn reserve_listener(&mut self) -> Result<std::net::TcpListener> {
+        let addr = format!("{}:{}", self.server.host, self.server.port);
+        let socket_addr: std::net::SocketAddr = addr
+            .parse()
+            .map_err(|e| anyhow::anyhow!("invalid server address {addr}: {e}"))?;
+
+        let domain = if socket_addr.is_ipv6() {
+            socket2::Domain::IPV6
+        } else {
+            socket2::Domain::IPV4
+        };
+        let socket = socket2::Socket::new(domain, socket2::Type::STREAM, Some(socket2::Protocol::TCP))?;
+        socket.set_reuse_address(true)?;
+
+        socket.bind(&socket_addr.into()).map_err(|e| {
+            if e.kind() == std::io::ErrorKind::AddrInUse {
+                anyhow::anyhow!("port {} already in use", self.server.port)
+            } else {
+                anyhow::anyhow!("failed to bind {addr}: {e}")
+            }
+        })?;
+        socket.listen(1024)?;
+
+        let listener: std::net::TcpListener = socket.into();
+        let bound_port = listener.local_addr()?.port();
 
         if self.server.port == 0 {
-            return Err(anyhow::anyhow!("Server port must be greater than 0"));
+            info!("Reserved ephemeral port {bound_port} for server.port = 0");
+            self.server.port = bound_port;
+        }
+
+        Ok(listener)
+    }
+
+    /// Checks every field in one pass and returns every problem found,
+    /// rather than stopping at the first one - so an operator (or the
+    /// hot-reload watcher rejecting a bad file) can see the whole picture
+    /// instead of fixing issues one `cargo run` at a time. Hard issues
+    /// (`ValidationSeverity::Error`) are what `validate()` treats as fatal;
+    /// warnings (e.g. routing timeout exceeding request timeout) are
+    /// informational and never fail validation on their own.
+    pub fn validate_all(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+
+        macro_rules! issue {
+            ($severity:expr, $field:expr, $msg:expr) => {
+                issues.push(ValidationIssue {
+                    field: $field.to_string(),
+                    message: $msg.to_string(),
+                    severity: $severity,
+                });
+            };
+        }
+
+        if self.deepseek.api_key.is_empty() {
+            issue!(ValidationSeverity::Error, "deepseek.api_key", "DeepSeek API key is required");
         }
 
         if self.server.workers == 0 {
-            return Err(anyhow::anyhow!("Server workers must be greater than 0"));
+            issue!(ValidationSeverity::Error, "server.workers", "Server workers must be greater than 0");
+        }
+
+        if self.server.transport != "stdio" && self.server.transport != "http" {
+            issue!(
+                ValidationSeverity::Error,
+                "server.transport",
+                format!("Server transport must be \"stdio\" or \"http\", got \"{}\"", self.server.transport)
+            );
         }
 
         if self.deepseek.max_tokens == 0 {
-            return Err(anyhow::anyhow!("DeepSeek max tokens must be greater than 0"));
+            issue!(ValidationSeverity::Error, "deepseek.max_tokens", "DeepSeek max tokens must be greater than 0");
         }
 
         if self.deepseek.temperature < 0.0 || self.deepseek.temperature > 2.0 {
-            return Err(anyhow::anyhow!("DeepSeek temperature must be between 0.0 and 2.0"));
+            issue!(ValidationSeverity::Error, "deepseek.temperature", "DeepSeek temperature must be between 0.0 and 2.0");
+        }
+
+        if self.server.tcp.keepalive_enabled && self.server.tcp.keepalive_idle_seconds == 0 {
+            issue!(
+                ValidationSeverity::Error,
+                "server.tcp.keepalive_idle_seconds",
+                "Keepalive idle seconds must be greater than 0 when keepalive is enabled"
+            );
+        }
+
+        if self.server.tcp.keepalive_enabled && self.server.tcp.keepalive_probes == 0 {
+            issue!(
+                ValidationSeverity::Error,
+                "server.tcp.keepalive_probes",
+                "Keepalive probe count must be greater than 0 when keepalive is enabled"
+            );
         }
 
-        // Validate performance configuration
         if self.performance.connection_pool_size == 0 {
-            return Err(anyhow::anyhow!("Connection pool size must be greater than 0"));
+            issue!(ValidationSeverity::Error, "performance.connection_pool_size", "Connection pool size must be greater than 0");
+        }
+
+        if self.performance.worker_threads == 0 {
+            issue!(ValidationSeverity::Error, "performance.worker_threads", "Worker threads must be greater than 0");
         }
 
         if self.performance.routing_timeout_ms > self.performance.request_timeout_ms {
-            warn!("Routing timeout ({} ms) is greater than request timeout ({} ms)", 
-                self.performance.routing_timeout_ms, self.performance.request_timeout_ms);
+            issue!(
+                ValidationSeverity::Warning,
+                "performance.routing_timeout_ms",
+                format!(
+                    "Routing timeout ({} ms) is greater than request timeout ({} ms)",
+                    self.performance.routing_timeout_ms, self.performance.request_timeout_ms
+                )
+            );
         }
 
         if self.cache.enabled && self.cache.max_entries == 0 {
-            return Err(anyhow::anyhow!("Cache max entries must be greater than 0 when cache is enabled"));
+            issue!(ValidationSeverity::Error, "cache.max_entries", "Cache max entries must be greater than 0 when cache is enabled");
+        }
+
+        if self.compression.enabled && self.compression.algorithms.is_empty() {
+            issue!(ValidationSeverity::Error, "compression.algorithms", "At least one compression algorithm is required when compression is enabled");
+        }
+
+        for algorithm in &self.compression.algorithms {
+            if !["gzip", "deflate", "zstd"].contains(&algorithm.as_str()) {
+                issue!(
+                    ValidationSeverity::Error,
+                    "compression.algorithms",
+                    format!("Unsupported compression algorithm \"{}\" (expected gzip, deflate, or zstd)", algorithm)
+                );
+            }
+        }
+
+        if self.compression.level > 9 {
+            issue!(
+                ValidationSeverity::Warning,
+                "compression.level",
+                format!("Compression level {} is above the usual 0-9 range and will be clamped to the encoder's maximum", self.compression.level)
+            );
         }
 
         if self.circuit_breaker.enabled && self.circuit_breaker.failure_threshold == 0 {
-            return Err(anyhow::anyhow!("Circuit breaker failure threshold must be greater than 0 when enabled"));
+            issue!(
+                ValidationSeverity::Error,
+                "circuit_breaker.failure_threshold",
+                "Circuit breaker failure threshold must be greater than 0 when enabled"
+            );
+        }
+
+        if self.audit_sink.enabled && self.audit_sink.endpoint.is_empty() {
+            issue!(ValidationSeverity::Error, "audit_sink.endpoint", "Audit sink endpoint is required when audit sink is enabled");
         }
 
-        Ok(())
+        if self.notify.webhook_enabled && self.notify.webhook_url.is_empty() {
+            issue!(ValidationSeverity::Error, "notify.webhook_url", "Notify webhook URL is required when the notify webhook is enabled");
+        }
+
+        issues
     }
 
     pub fn deepseek_api_key(&self) -> &str {
@@ -367,6 +574,10 @@ impl Config {
         Duration::from_secs(self.cache.ttl_seconds)
     }
 
+    pub fn get_graceful_shutdown_timeout(&self) -> Duration {
+        Duration::from_millis(self.performance.graceful_shutdown_timeout_ms)
+    }
+
     pub fn performance_summary(&self) -> String {
         format!(
             "Performance Config - Pool: {}, Concurrent: {}, Routing: {}ms, Cache: {} (TTL: {}s), Circuit Breaker: {}",
@@ -378,4 +589,673 @@ impl Config {
             if self.circuit_breaker.enabled { "enabled" } else { "disabled" }
         )
     }
+
+    /// Watches `path` for changes, re-parsing and re-validating the file on
+    /// each write and publishing the result over a `tokio::sync::watch`
+    /// channel. Subsystems that want to reconfigure live - the cache picking
+    /// up a new `ttl_seconds`, the circuit breaker a new
+    /// `failure_threshold` - should `.subscribe()` the returned receiver
+    /// instead of requiring a restart. A reload that fails to parse or
+    /// validate is logged via `tracing::warn` and the last-good config keeps
+    /// serving. Returns the initial config already loaded as the receiver's
+    /// current value, plus a handle that keeps the watcher and background
+    /// task alive for as long as it's held.
+    pub fn watch(path: PathBuf) -> Result<(watch::Receiver<Arc<Config>>, ConfigWatcherHandle)> {
+        let initial = Self::load_from_file(&path)?;
+        initial.validate()?;
+        let (tx, rx) = watch::channel(Arc::new(initial));
+
+        let (event_tx, mut event_rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+            match res {
+                Ok(event) if event.kind.is_modify() || event.kind.is_create() => {
+                    let _ = event_tx.send(());
+                }
+                Ok(_) => {}
+                Err(e) => warn!("Config file watcher error: {}", e),
+            }
+        })?;
+        watcher.watch(&path, notify::RecursiveMode::NonRecursive)?;
+
+        let watch_path = path.clone();
+        let task = tokio::spawn(async move {
+            while event_rx.recv().await.is_some() {
+                // Coalesce the rest of this burst of events - an editor's
+                // save is often several writes in quick succession - into a
+                // single reload.
+                while tokio::time::timeout(RELOAD_DEBOUNCE, event_rx.recv()).await.is_ok() {}
+
+                match Self::load_from_file(&watch_path) {
+                    Ok(config) => {
+                        let issues = config.validate_all();
+                        if issues.iter().any(|issue| issue.severity == ValidationSeverity::Error) {
+                            for issue in &issues {
+                                warn!("Rejected reload of {:?}: {}", watch_path, issue);
+                            }
+                        } else {
+                            for issue in &issues {
+                                warn!("Reloaded {:?} with a warning: {}", watch_path, issue);
+                            }
+                            info!("Reloaded configuration from {:?}", watch_path);
+                            let _ = tx.send(Arc::new(config));
+                        }
+                    }
+                    Err(e) => {
+                        warn!(
+                            "Failed to reload configuration from {:?}: {} (keeping previous configuration)",
+                            watch_path, e
+                        );
+                    }
+                }
+            }
+        });
+
+        Ok((rx, ConfigWatcherHandle { _watcher: watcher, _task: task }))
+    }
+}
+
+/// Keeps `Config::watch`'s background file watcher and reload task alive;
+/// dropping this handle stops watching the file.
+pub struct ConfigWatcherHandle {
+    _watcher: notify::RecommendedWatcher,
+    _task: tokio::task::JoinHandle<()>,
+}
+
+/// Which layer of a `ConfigBuilder` supplied a given value or problem, in
+/// the order later layers override earlier ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigLayer {
+    Defaults,
+    File,
+    Environment,
+    Override,
+    /// The fully-merged config failed a cross-field check that isn't
+    /// attributable to a single source layer.
+    Resolved,
+}
+
+impl std::fmt::Display for ConfigLayer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ConfigLayer::Defaults => "defaults",
+            ConfigLayer::File => "file",
+            ConfigLayer::Environment => "environment",
+            ConfigLayer::Override => "override",
+            ConfigLayer::Resolved => "resolved",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A single problem found while building or validating a layered config,
+/// carrying enough context - which field, which layer supplied it, and the
+/// offending value where one is available - for an operator to fix every
+/// misconfiguration in one pass instead of one `cargo run` at a time.
+#[derive(Debug, Clone)]
+pub struct ConfigError {
+    pub field_path: String,
+    pub message: String,
+    pub layer: ConfigLayer,
+    pub value: Option<serde_json::Value>,
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}] {}: {}", self.layer, self.field_path, self.message)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Merges `overlay` into `base` key-by-key: nested objects are merged
+/// recursively so a partial document (e.g. a file that only sets
+/// `cache.ttl_seconds`) only touches the keys it mentions, while any other
+/// value type simply replaces whatever `base` held.
+fn deep_merge(base: &mut serde_json::Value, overlay: serde_json::Value) {
+    match (base, overlay) {
+        (serde_json::Value::Object(base_map), serde_json::Value::Object(overlay_map)) => {
+            for (key, value) in overlay_map {
+                match base_map.get_mut(&key) {
+                    Some(existing) => deep_merge(existing, value),
+                    None => {
+                        base_map.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base_slot, overlay_value) => {
+            *base_slot = overlay_value;
+        }
+    }
+}
+
+/// Sets `value` at the dotted `path` within `root`, creating intermediate
+/// objects as needed.
+fn set_path(root: &mut serde_json::Value, path: &[&str], value: serde_json::Value) {
+    let mut current = root;
+    for segment in &path[..path.len() - 1] {
+        if !current.is_object() {
+            *current = serde_json::Value::Object(serde_json::Map::new());
+        }
+        current = current
+            .as_object_mut()
+            .expect("just ensured object")
+            .entry(segment.to_string())
+            .or_insert_with(|| serde_json::Value::Object(serde_json::Map::new()));
+    }
+    if let Some(last) = path.last() {
+        if !current.is_object() {
+            *current = serde_json::Value::Object(serde_json::Map::new());
+        }
+        current.as_object_mut().expect("just ensured object").insert(last.to_string(), value);
+    }
+}
+
+/// The JSON type a given environment variable should be parsed as before
+/// being merged into the config tree.
+#[derive(Debug, Clone, Copy)]
+enum EnvKind {
+    Str,
+    /// Comma-separated list, e.g. `COMPRESSION_ALGORITHMS=gzip,deflate`. Empty
+    /// segments (from trailing/doubled commas) are dropped rather than
+    /// producing empty-string list entries.
+    StrList,
+    Bool,
+    U16,
+    U32,
+    U64,
+    USize,
+    F32,
+    I64,
+}
+
+fn parse_env_value(kind: EnvKind, raw: &str) -> Option<serde_json::Value> {
+    match kind {
+        EnvKind::Str => Some(serde_json::Value::String(raw.to_string())),
+        EnvKind::StrList => Some(serde_json::Value::Array(
+            raw.split(',')
+                .map(|s| s.trim())
+                .filter(|s| !s.is_empty())
+                .map(|s| serde_json::Value::String(s.to_string()))
+                .collect(),
+        )),
+        EnvKind::Bool => raw.parse::<bool>().ok().map(serde_json::Value::Bool),
+        EnvKind::U16 => raw.parse::<u16>().ok().map(|v| serde_json::json!(v)),
+        EnvKind::U32 => raw.parse::<u32>().ok().map(|v| serde_json::json!(v)),
+        EnvKind::U64 => raw.parse::<u64>().ok().map(|v| serde_json::json!(v)),
+        EnvKind::USize => raw.parse::<usize>().ok().map(|v| serde_json::json!(v)),
+        EnvKind::F32 => raw.parse::<f32>().ok().map(|v| serde_json::json!(v)),
+        EnvKind::I64 => raw.parse::<i64>().ok().map(|v| serde_json::json!(v)),
+    }
+}
+
+/// Maps every environment variable the bridge understands to its place in
+/// the config tree, so `ConfigBuilder`'s environment layer can overlay only
+/// the variables that are actually set rather than reconstructing
+/// `defaults_value`'s whole-document defaults.
+const ENV_VAR_TABLE: &[(&[&str], &str, EnvKind)] = &[
+    (&["server", "host"], "HOST", EnvKind::Str),
+    (&["server", "port"], "PORT", EnvKind::U16),
+    (&["server", "workers"], "WORKERS", EnvKind::USize),
+    (&["server", "timeout_seconds"], "TIMEOUT_SECONDS", EnvKind::U64),
+    (&["server", "max_connections"], "MAX_CONNECTIONS", EnvKind::USize),
+    (&["server", "transport"], "MCP_TRANSPORT", EnvKind::Str),
+    (&["server", "tcp", "fast_open_backlog"], "TCP_FASTOPEN_BACKLOG", EnvKind::U32),
+    (&["server", "tcp", "keepalive_enabled"], "TCP_KEEPALIVE_ENABLED", EnvKind::Bool),
+    (&["server", "tcp", "keepalive_idle_seconds"], "TCP_KEEPALIVE_IDLE_SECONDS", EnvKind::U64),
+    (&["server", "tcp", "keepalive_interval_seconds"], "TCP_KEEPALIVE_INTERVAL_SECONDS", EnvKind::U64),
+    (&["server", "tcp", "keepalive_probes"], "TCP_KEEPALIVE_PROBES", EnvKind::U32),
+    (&["server", "tcp", "nodelay"], "TCP_NODELAY", EnvKind::Bool),
+    (&["server", "tcp", "collect_tcp_info"], "TCP_COLLECT_INFO", EnvKind::Bool),
+    (&["deepseek", "api_key"], "DEEPSEEK_API_KEY", EnvKind::Str),
+    (&["deepseek", "base_url"], "DEEPSEEK_BASE_URL", EnvKind::Str),
+    (&["deepseek", "model"], "DEEPSEEK_MODEL", EnvKind::Str),
+    (&["deepseek", "max_tokens"], "DEEPSEEK_MAX_TOKENS", EnvKind::U32),
+    (&["deepseek", "temperature"], "DEEPSEEK_TEMPERATURE", EnvKind::F32),
+    (&["deepseek", "timeout_seconds"], "DEEPSEEK_TIMEOUT_SECONDS", EnvKind::U64),
+    (&["deepseek", "retry_attempts"], "DEEPSEEK_RETRY_ATTEMPTS", EnvKind::USize),
+    (&["deepseek", "rate_limit_per_minute"], "DEEPSEEK_RATE_LIMIT_PER_MINUTE", EnvKind::U32),
+    (&["metrics", "otlp", "enabled"], "OTLP_ENABLED", EnvKind::Bool),
+    (&["metrics", "otlp", "endpoint"], "OTLP_ENDPOINT", EnvKind::Str),
+    (&["metrics", "otlp", "interval_seconds"], "OTLP_INTERVAL_SECONDS", EnvKind::U64),
+    (&["metrics", "otlp", "service_name"], "OTLP_SERVICE_NAME", EnvKind::Str),
+    (&["metrics", "consumption", "enabled"], "CONSUMPTION_UPLOAD_ENABLED", EnvKind::Bool),
+    (&["metrics", "consumption", "endpoint"], "CONSUMPTION_UPLOAD_ENDPOINT", EnvKind::Str),
+    (&["metrics", "consumption", "interval_seconds"], "CONSUMPTION_UPLOAD_INTERVAL_SECONDS", EnvKind::U64),
+    (&["metrics", "consumption", "node_id"], "CONSUMPTION_NODE_ID", EnvKind::Str),
+    (&["metrics", "consumption", "chunk_size"], "CONSUMPTION_CHUNK_SIZE", EnvKind::USize),
+    (&["metrics", "consumption", "cache_path"], "CONSUMPTION_CACHE_PATH", EnvKind::Str),
+    (&["performance", "connection_pool_size"], "CONNECTION_POOL_SIZE", EnvKind::USize),
+    (&["performance", "max_concurrent_requests"], "MAX_CONCURRENT_REQUESTS", EnvKind::USize),
+    (&["performance", "request_timeout_ms"], "REQUEST_TIMEOUT_MS", EnvKind::U64),
+    (&["performance", "routing_timeout_ms"], "ROUTING_TIMEOUT_MS", EnvKind::U64),
+    (&["performance", "file_processing_concurrency"], "FILE_PROCESSING_CONCURRENCY", EnvKind::USize),
+    (&["performance", "enable_request_deduplication"], "ENABLE_REQUEST_DEDUPLICATION", EnvKind::Bool),
+    (&["performance", "enable_streaming"], "ENABLE_STREAMING", EnvKind::Bool),
+    (&["performance", "worker_threads"], "WORKER_THREADS", EnvKind::USize),
+    (&["performance", "graceful_shutdown_timeout_ms"], "GRACEFUL_SHUTDOWN_TIMEOUT_MS", EnvKind::U64),
+    (&["cache", "enabled"], "CACHE_ENABLED", EnvKind::Bool),
+    (&["cache", "ttl_seconds"], "CACHE_TTL_SECONDS", EnvKind::U64),
+    (&["cache", "max_entries"], "CACHE_MAX_ENTRIES", EnvKind::USize),
+    (&["cache", "cache_response_bodies"], "CACHE_RESPONSE_BODIES", EnvKind::Bool),
+    (&["cache", "cache_file_contents"], "CACHE_FILE_CONTENTS", EnvKind::Bool),
+    (&["cache", "invalidation_strategy"], "CACHE_INVALIDATION_STRATEGY", EnvKind::Str),
+    (&["compression", "enabled"], "COMPRESSION_ENABLED", EnvKind::Bool),
+    (&["compression", "algorithms"], "COMPRESSION_ALGORITHMS", EnvKind::StrList),
+    (&["compression", "min_size_bytes"], "COMPRESSION_MIN_SIZE_BYTES", EnvKind::USize),
+    (&["compression", "level"], "COMPRESSION_LEVEL", EnvKind::U32),
+    (&["circuit_breaker", "enabled"], "CIRCUIT_BREAKER_ENABLED", EnvKind::Bool),
+    (&["circuit_breaker", "failure_threshold"], "CIRCUIT_BREAKER_FAILURE_THRESHOLD", EnvKind::USize),
+    (&["circuit_breaker", "recovery_timeout_seconds"], "CIRCUIT_BREAKER_RECOVERY_TIMEOUT", EnvKind::U64),
+    (&["circuit_breaker", "half_open_max_calls"], "CIRCUIT_BREAKER_HALF_OPEN_MAX_CALLS", EnvKind::USize),
+    (&["circuit_breaker", "timeout_duration_ms"], "CIRCUIT_BREAKER_TIMEOUT_MS", EnvKind::U64),
+    (&["audit_sink", "enabled"], "AUDIT_SINK_ENABLED", EnvKind::Bool),
+    (&["audit_sink", "endpoint"], "AUDIT_SINK_ENDPOINT", EnvKind::Str),
+    (&["audit_sink", "buffer_size"], "AUDIT_SINK_BUFFER_SIZE", EnvKind::USize),
+    (&["audit_sink", "syslog_enabled"], "SYSLOG_ENABLED", EnvKind::Bool),
+    (&["audit_sink", "syslog_address"], "SYSLOG_ADDRESS", EnvKind::Str),
+    (&["audit_sink", "file_path"], "LOG_FILE", EnvKind::Str),
+    (&["audit_sink", "file_max_bytes"], "AUDIT_FILE_MAX_BYTES", EnvKind::U64),
+    (&["notify", "webhook_enabled"], "NOTIFY_WEBHOOK_ENABLED", EnvKind::Bool),
+    (&["notify", "webhook_url"], "NOTIFY_WEBHOOK_URL", EnvKind::Str),
+    (&["notify", "risk_score_threshold"], "NOTIFY_RISK_SCORE_THRESHOLD", EnvKind::U32),
+    (&["notify", "debounce_seconds"], "NOTIFY_DEBOUNCE_SECONDS", EnvKind::I64),
+];
+
+/// The built-in, hardcoded defaults for every field - independent of any
+/// environment variable - except for the handful that fork on `env`
+/// ("production" vs everything else).
+fn defaults_value(env: &str) -> serde_json::Value {
+    let is_production = env == "production";
+    serde_json::json!({
+        "server": {
+            "host": "127.0.0.1",
+            "port": 8080,
+            "workers": 4,
+            "timeout_seconds": 30,
+            "max_connections": 1000,
+            "cors_enabled": !is_production,
+            "transport": "stdio",
+            "tcp": {
+                "fast_open_backlog": null,
+                "keepalive_enabled": true,
+                "keepalive_idle_seconds": 60,
+                "keepalive_interval_seconds": 10,
+                "keepalive_probes": 3,
+                "nodelay": true,
+                "collect_tcp_info": false,
+            },
+        },
+        "deepseek": {
+            "api_key": "",
+            "base_url": "https://api.deepseek.com",
+            "model": "deepseek-chat",
+            "max_tokens": 4096,
+            "temperature": 0.7,
+            "timeout_seconds": 60,
+            "retry_attempts": 3,
+            "rate_limit_per_minute": 60,
+        },
+        "mcp": {
+            "protocol_version": "2024-11-05",
+            "capabilities": { "tools": true, "resources": true, "prompts": true, "sampling": true },
+            "tools": ["search", "analyze", "generate"],
+            "resources": ["files", "projects"],
+        },
+        "logging": {
+            "level": if is_production { "info" } else { "debug" },
+            "format": "json",
+            "file": if is_production { Some("/var/log/deepseek-mcp-bridge.log") } else { None },
+            "rotation": true,
+            "max_size_mb": 100,
+            "max_files": 10,
+        },
+        "metrics": {
+            "enabled": is_production,
+            "port": 9090,
+            "path": "/metrics",
+            "collect_detailed": !is_production,
+            "otlp": {
+                "enabled": false,
+                "endpoint": "http://localhost:4318",
+                "interval_seconds": 60,
+                "service_name": "deepseek-mcp-bridge",
+                "service_version": env!("CARGO_PKG_VERSION"),
+            },
+            "consumption": {
+                "enabled": false,
+                "endpoint": "https://billing.internal/v1/usage",
+                "interval_seconds": 300,
+                "node_id": std::env::var("HOSTNAME").unwrap_or_else(|_| "local-node".to_string()),
+                "chunk_size": 1000,
+                "cache_path": "/tmp/deepseek-mcp-bridge-consumption-cache.json",
+            },
+        },
+        "performance": {
+            "connection_pool_size": 10,
+            "max_concurrent_requests": 100,
+            "request_timeout_ms": 30000,
+            "routing_timeout_ms": 100,
+            "file_processing_concurrency": 8,
+            "enable_request_deduplication": true,
+            "enable_streaming": true,
+            "worker_threads": std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4),
+            "graceful_shutdown_timeout_ms": 5000,
+        },
+        "cache": {
+            "enabled": true,
+            "ttl_seconds": 300,
+            "max_entries": 1000,
+            "cache_response_bodies": true,
+            "cache_file_contents": true,
+            "invalidation_strategy": "ttl",
+        },
+        "compression": {
+            "enabled": true,
+            "algorithms": ["gzip", "deflate", "zstd"],
+            "min_size_bytes": 512,
+            "level": 6,
+        },
+        "circuit_breaker": {
+            "enabled": true,
+            "failure_threshold": 5,
+            "recovery_timeout_seconds": 60,
+            "half_open_max_calls": 3,
+            "timeout_duration_ms": 5000,
+        },
+        "audit_sink": {
+            "enabled": false,
+            "endpoint": "",
+            "buffer_size": 1024,
+            "syslog_enabled": false,
+            "syslog_address": "127.0.0.1:514",
+            "file_path": null,
+            "file_max_bytes": 10 * 1024 * 1024,
+        },
+        "notify": {
+            "webhook_enabled": false,
+            "webhook_url": "",
+            "risk_score_threshold": 75,
+            "debounce_seconds": 300,
+        },
+    })
+}
+
+/// Overlays only the environment variables that are actually set, so it can
+/// sit between the `file` and `override` layers without clobbering either
+/// with unset-variable defaults. Variables that are set but fail to parse
+/// as their expected type are reported as `ConfigError`s rather than
+/// silently ignored or falling back to a default.
+fn env_layer_value(errors: &mut Vec<ConfigError>) -> serde_json::Value {
+    let mut value = serde_json::json!({});
+
+    for (path, env_var, kind) in ENV_VAR_TABLE {
+        if let Ok(raw) = std::env::var(env_var) {
+            match parse_env_value(*kind, &raw) {
+                Some(parsed) => set_path(&mut value, path, parsed),
+                None => errors.push(ConfigError {
+                    field_path: path.join("."),
+                    message: format!("{} is set to {:?}, which doesn't parse as the expected type", env_var, raw),
+                    layer: ConfigLayer::Environment,
+                    value: Some(serde_json::Value::String(raw)),
+                }),
+            }
+        }
+    }
+
+    value
+}
+
+/// Builds a `Config` by merging ordered layers - built-in defaults, an
+/// optional file, environment variables, then explicit overrides - where
+/// each later layer only replaces the keys it sets rather than the whole
+/// document, so a file that only contains `{"cache": {"ttl_seconds": 60}}`
+/// is valid on its own. Every problem found across every layer is collected
+/// into a `Vec<ConfigError>` and returned together instead of stopping at
+/// the first one, so an operator sees every misconfiguration in one pass.
+#[derive(Debug, Clone)]
+pub struct ConfigBuilder {
+    env: String,
+    file_path: Option<PathBuf>,
+    overrides: serde_json::Value,
+}
+
+impl ConfigBuilder {
+    pub fn new(env: &str) -> Self {
+        Self {
+            env: env.to_string(),
+            file_path: None,
+            overrides: serde_json::json!({}),
+        }
+    }
+
+    pub fn with_file(mut self, path: PathBuf) -> Self {
+        self.file_path = Some(path);
+        self
+    }
+
+    /// Merges `value` over the file/environment layers, key-by-key, for
+    /// callers that have a handful of specific overrides (tests, CLI flags)
+    /// rather than a whole file.
+    pub fn with_override(mut self, value: serde_json::Value) -> Self {
+        deep_merge(&mut self.overrides, value);
+        self
+    }
+
+    pub fn build(self) -> Result<Config, Vec<ConfigError>> {
+        let mut merged = defaults_value(&self.env);
+        let mut errors = Vec::new();
+
+        if let Some(path) = &self.file_path {
+            match std::fs::read_to_string(path) {
+                Ok(content) => match format_for_path(path).and_then(|format| parse_config_value(format, &content)) {
+                    Ok(file_value) => deep_merge(&mut merged, file_value),
+                    Err(e) => errors.push(ConfigError {
+                        field_path: "$".to_string(),
+                        message: e.to_string(),
+                        layer: ConfigLayer::File,
+                        value: None,
+                    }),
+                },
+                Err(e) => errors.push(ConfigError {
+                    field_path: "$".to_string(),
+                    message: format!("could not read {:?}: {}", path, e),
+                    layer: ConfigLayer::File,
+                    value: None,
+                }),
+            }
+        }
+
+        deep_merge(&mut merged, env_layer_value(&mut errors));
+        deep_merge(&mut merged, self.overrides.clone());
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        let config: Config = match serde_json::from_value(merged) {
+            Ok(config) => config,
+            Err(e) => {
+                return Err(vec![ConfigError {
+                    field_path: "$".to_string(),
+                    message: format!("does not match the Config schema: {}", e),
+                    layer: ConfigLayer::Override,
+                    value: None,
+                }]);
+            }
+        };
+
+        // Only hard issues fail the build; validate_all's warnings (e.g. a
+        // routing timeout exceeding the request timeout) are left for the
+        // caller to log if it cares, same as validate() itself.
+        let hard_issues: Vec<ConfigError> = config
+            .validate_all()
+            .into_iter()
+            .filter(|issue| issue.severity == ValidationSeverity::Error)
+            .map(|issue| ConfigError {
+                field_path: issue.field,
+                message: issue.message,
+                layer: ConfigLayer::Resolved,
+                value: None,
+            })
+            .collect();
+
+        if !hard_issues.is_empty() {
+            return Err(hard_issues);
+        }
+
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `#[test]`s run in parallel by default, but the tests below mutate
+    /// shared process environment variables that `Config::load`/`ConfigBuilder`'s
+    /// env layer also read - without serializing those mutations, a set_var
+    /// in one test can race a read (or another test's own
+    /// set_var/remove_var) in the other. Every test touching process env
+    /// should acquire this before mutating and hold it until the env is
+    /// restored.
+    static ENV_MUTEX: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    #[test]
+    fn deep_merge_overrides_only_mentioned_keys() {
+        let mut base = serde_json::json!({"cache": {"enabled": true, "ttl_seconds": 300}, "server": {"port": 8080}});
+        let overlay = serde_json::json!({"cache": {"ttl_seconds": 60}});
+        deep_merge(&mut base, overlay);
+
+        assert_eq!(base["cache"]["ttl_seconds"], 60);
+        assert_eq!(base["cache"]["enabled"], true);
+        assert_eq!(base["server"]["port"], 8080);
+    }
+
+    #[test]
+    fn builder_layers_override_in_order() {
+        let _guard = ENV_MUTEX.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        std::env::set_var("DEEPSEEK_API_KEY", "env-key");
+        std::env::set_var("CACHE_TTL_SECONDS", "42");
+
+        let result = ConfigBuilder::new("test")
+            .with_override(serde_json::json!({"cache": {"ttl_seconds": 99}}))
+            .build();
+
+        std::env::remove_var("DEEPSEEK_API_KEY");
+        std::env::remove_var("CACHE_TTL_SECONDS");
+
+        let config = result.expect("layered config should build");
+        assert_eq!(config.deepseek.api_key, "env-key");
+        // The explicit override layer wins over the environment layer.
+        assert_eq!(config.cache.ttl_seconds, 99);
+    }
+
+    #[test]
+    fn builder_collects_every_validation_error() {
+        let _guard = ENV_MUTEX.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        std::env::set_var("DEEPSEEK_MAX_TOKENS", "0");
+
+        let result = ConfigBuilder::new("test")
+            .with_override(serde_json::json!({"server": {"workers": 0}}))
+            .build();
+
+        std::env::remove_var("DEEPSEEK_MAX_TOKENS");
+
+        let errors = result.expect_err("missing api key, zero workers and zero max_tokens should all fail");
+        assert!(errors.iter().any(|e| e.field_path == "deepseek.api_key"));
+        assert!(errors.iter().any(|e| e.field_path == "server.workers"));
+        assert!(errors.iter().any(|e| e.field_path == "deepseek.max_tokens"));
+    }
+
+    #[test]
+    fn reserve_listener_rewrites_port_zero_to_the_bound_ephemeral_port() {
+        let mut config = ConfigBuilder::new("test")
+            .with_override(serde_json::json!({"deepseek": {"api_key": "k"}, "server": {"port": 0}}))
+            .build()
+            .expect("valid config with port 0 should build");
+
+        let listener = config.reserve_listener().expect("binding an ephemeral port should succeed");
+
+        assert_ne!(config.server.port, 0);
+        assert_eq!(listener.local_addr().unwrap().port(), config.server.port);
+    }
+
+    #[test]
+    fn reserve_listener_reports_port_already_in_use() {
+        let mut first = ConfigBuilder::new("test")
+            .with_override(serde_json::json!({"deepseek": {"api_key": "k"}, "server": {"port": 0}}))
+            .build()
+            .expect("valid config with port 0 should build");
+        let _held_listener = first.reserve_listener().expect("first bind should succeed");
+
+        let mut second = ConfigBuilder::new("test")
+            .with_override(serde_json::json!({"deepseek": {"api_key": "k"}, "server": {"port": first.server.port}}))
+            .build()
+            .expect("valid config for the same port should build");
+
+        let err = second.reserve_listener().expect_err("binding the same port twice should fail");
+        assert!(err.to_string().contains("already in use"), "unexpected error: {}", err);
+    }
+
+    #[test]
+    fn builder_reports_invalid_file_json() {
+        let path = std::env::temp_dir().join(format!("config-builder-test-{}.json", std::process::id()));
+        std::fs::write(&path, "{not valid json").unwrap();
+
+        let result = ConfigBuilder::new("test").with_file(path.clone()).build();
+
+        std::fs::remove_file(&path).ok();
+
+        let errors = result.expect_err("invalid JSON file should fail");
+        assert!(errors.iter().any(|e| e.layer == ConfigLayer::File));
+    }
+
+    #[test]
+    fn builder_merges_a_partial_yaml_file() {
+        let path = std::env::temp_dir().join(format!("config-builder-test-{}.yaml", std::process::id()));
+        std::fs::write(&path, "cache:\n  ttl_seconds: 123\n").unwrap();
+
+        let result = ConfigBuilder::new("test")
+            .with_file(path.clone())
+            .with_override(serde_json::json!({"deepseek": {"api_key": "present"}}))
+            .build();
+
+        std::fs::remove_file(&path).ok();
+
+        let config = result.expect("partial YAML file should merge over defaults");
+        assert_eq!(config.cache.ttl_seconds, 123);
+        // Unmentioned keys still come from the defaults/env layers.
+        assert_eq!(config.server.port, 8080);
+    }
+
+    #[test]
+    fn to_format_round_trips_through_each_format() {
+        let config = ConfigBuilder::new("test")
+            .with_override(serde_json::json!({"deepseek": {"api_key": "present"}}))
+            .build()
+            .expect("valid layered config should build");
+
+        for format in [Format::Json, Format::Yaml, Format::Toml] {
+            let dumped = config.to_format(format).expect("serializing should succeed");
+            let parsed = parse_config_str(format, &dumped).expect("round-tripping should succeed");
+            assert_eq!(parsed.deepseek.api_key, "present");
+        }
+    }
+
+    #[test]
+    fn validate_all_separates_errors_from_warnings() {
+        let mut config = ConfigBuilder::new("test")
+            .with_override(serde_json::json!({"deepseek": {"api_key": "present"}}))
+            .build()
+            .expect("valid layered config should build");
+        config.performance.routing_timeout_ms = config.performance.request_timeout_ms + 1;
+
+        let issues = config.validate_all();
+        assert!(issues.iter().all(|i| i.severity == ValidationSeverity::Warning));
+        assert!(config.validate().is_ok(), "a warning-only config should still pass validate()");
+
+        config.deepseek.api_key.clear();
+        assert!(config.validate().is_err(), "an empty api_key is a hard error");
+    }
 }
\ No newline at end of file
@@ -1,6 +1,7 @@
 use anyhow::Result;
 use reqwest::{Client, header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE}};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use std::sync::atomic::{AtomicUsize, AtomicBool, Ordering};
@@ -53,12 +54,53 @@ pub struct DeepSeekRequest {
     pub max_tokens: Option<u32>,
     pub temperature: Option<f32>,
     pub stream: Option<bool>,
+    /// OpenAI-style function schemas (`{"type": "function", "function": {...}}`),
+    /// set when a caller wants DeepSeek able to invoke the server's own tools
+    /// instead of just returning text. `None` omits the field entirely so
+    /// existing non-tool-calling requests are unaffected.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub tools: Option<Vec<Value>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Message {
     pub role: String,
     pub content: String,
+    /// Present on an assistant message that chose to call one or more tools
+    /// instead of (or alongside) returning text.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub tool_calls: Option<Vec<ToolCall>>,
+    /// Present on a `role: "tool"` message, linking its content back to the
+    /// `ToolCall::id` it answers.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub tool_call_id: Option<String>,
+    /// Present on a `role: "tool"` message alongside `tool_call_id`; not all
+    /// OpenAI-compatible backends require it, but several accept it.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub name: Option<String>,
+}
+
+impl Message {
+    pub fn user(content: String) -> Self {
+        Self { role: "user".to_string(), content, tool_calls: None, tool_call_id: None, name: None }
+    }
+}
+
+/// One function the model asked to invoke, as returned in
+/// `Choice::message::tool_calls`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCall {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub call_type: String,
+    pub function: FunctionCall,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionCall {
+    pub name: String,
+    /// JSON-encoded arguments, as emitted by the model - not yet parsed.
+    pub arguments: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -293,6 +335,128 @@ impl DeepSeekClient {
         unreachable!()
     }
 
+    /// Streams a chat completion instead of buffering the whole reply, invoking
+    /// `on_delta` with each content fragment as it arrives off the wire. Bypasses
+    /// the response cache used by `chat_completion` since a partially-streamed
+    /// reply can't be replayed from a cached snapshot, but is gated by the same
+    /// circuit breaker: a trip opened by `chat_completion` rejects streaming
+    /// requests too, and any streaming failure reports back to the breaker just
+    /// like a non-streamed one. Returns the fully assembled `DeepSeekResponse`
+    /// once the stream ends, for use in the final (non-streamed) MCP response.
+    pub async fn chat_completion_stream<F, Fut>(
+        &self,
+        request: DeepSeekRequest,
+        on_delta: F,
+    ) -> Result<DeepSeekResponse>
+    where
+        F: FnMut(String) -> Fut + Send,
+        Fut: std::future::Future<Output = ()> + Send,
+    {
+        if !self.circuit_breaker.can_execute() {
+            self.performance_metrics.circuit_breaker_trips.fetch_add(1, Ordering::Relaxed);
+            return Err(anyhow::anyhow!("Circuit breaker is open, request rejected"));
+        }
+
+        let result = self.chat_completion_stream_inner(request, on_delta).await;
+        match &result {
+            Ok(_) => self.circuit_breaker.record_success(),
+            Err(_) => self.circuit_breaker.record_failure(),
+        }
+        result
+    }
+
+    async fn chat_completion_stream_inner<F, Fut>(
+        &self,
+        mut request: DeepSeekRequest,
+        mut on_delta: F,
+    ) -> Result<DeepSeekResponse>
+    where
+        F: FnMut(String) -> Fut + Send,
+        Fut: std::future::Future<Output = ()> + Send,
+    {
+        use futures::StreamExt;
+
+        request.stream = Some(true);
+        self.performance_metrics.total_requests.fetch_add(1, Ordering::Relaxed);
+
+        let url = format!("{}/chat/completions", self.base_url);
+        let timeout_duration = self.config.get_request_timeout();
+
+        let response = timeout(timeout_duration, async {
+            self.client.post(&url).json(&request).send().await
+        }).await??;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            let sanitized_error = error_text
+                .replace(&self.config.deepseek.api_key, "[API_KEY_REDACTED]")
+                .chars()
+                .take(500)
+                .collect::<String>();
+
+            self.performance_metrics.failed_requests.fetch_add(1, Ordering::Relaxed);
+            error!("SECURITY: DeepSeek API streaming error {} (details sanitized)", status);
+            return Err(anyhow::anyhow!("DeepSeek API error {}: {}", status, sanitized_error));
+        }
+
+        let mut byte_stream = response.bytes_stream();
+        let mut buffer = String::new();
+        let mut content = String::new();
+        let mut response_id = String::new();
+        let mut created = 0u64;
+
+        while let Some(next) = byte_stream.next().await {
+            let bytes = next?;
+            buffer.push_str(&String::from_utf8_lossy(&bytes));
+
+            while let Some(boundary) = buffer.find("\n\n") {
+                let event: String = buffer.drain(..boundary + 2).collect();
+
+                for line in event.lines() {
+                    let Some(data) = line.strip_prefix("data: ") else { continue };
+                    if data == "[DONE]" {
+                        continue;
+                    }
+
+                    let chunk: serde_json::Value = serde_json::from_str(data)
+                        .map_err(|e| anyhow::anyhow!("Malformed DeepSeek stream chunk: {}", e))?;
+
+                    if response_id.is_empty() {
+                        response_id = chunk.get("id").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+                        created = chunk.get("created").and_then(|v| v.as_u64()).unwrap_or(0);
+                    }
+
+                    if let Some(delta) = chunk["choices"][0]["delta"]["content"].as_str() {
+                        content.push_str(delta);
+                        on_delta(delta.to_string()).await;
+                    }
+                }
+            }
+        }
+
+        self.performance_metrics.successful_requests.fetch_add(1, Ordering::Relaxed);
+
+        Ok(DeepSeekResponse {
+            id: response_id,
+            object: "chat.completion".to_string(),
+            created,
+            model: request.model,
+            choices: vec![Choice {
+                index: 0,
+                message: Message {
+                    role: "assistant".to_string(),
+                    content,
+                    tool_calls: None,
+                    tool_call_id: None,
+                    name: None,
+                },
+                finish_reason: Some("stop".to_string()),
+            }],
+            usage: None,
+        })
+    }
+
     async fn send_request(&self, url: &str, request: &DeepSeekRequest) -> Result<DeepSeekResponse> {
         let timeout_duration = self.config.get_request_timeout();
         
@@ -395,10 +559,7 @@ impl DeepSeekClient {
 
         // Perform actual health check
         let test_request = self.create_chat_request(vec![
-            Message {
-                role: "user".to_string(),
-                content: "ping".to_string(),
-            }
+            Message::user("ping".to_string())
         ]).await;
 
         match self.chat_completion(test_request).await {
@@ -422,6 +583,7 @@ impl DeepSeekClient {
             max_tokens: Some(self.config.deepseek.max_tokens),
             temperature: Some(self.config.deepseek.temperature),
             stream: Some(false),
+            tools: None,
         }
     }
 
@@ -0,0 +1,324 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tracing::{debug, warn};
+
+/// Rough chars-per-token ratio used everywhere we need a token estimate
+/// without pulling in a real tokenizer - good enough for chunk sizing and
+/// reporting, not meant to match the DeepSeek API's own accounting exactly.
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Knobs shared by `analyze_files` and `youtu_agent_analyze_files`, pulled
+/// out of each tool's `arguments` so `ingest_files` doesn't need to know
+/// which tool called it.
+#[derive(Debug, Clone)]
+pub struct IngestOptions {
+    pub pattern: Option<String>,
+    pub allowed_extensions: Option<Vec<String>>,
+    pub max_file_size: u64,
+    pub concurrency: usize,
+    pub max_files: Option<usize>,
+    pub chunking: Option<ChunkOptions>,
+    /// Every resolved path must canonicalize to somewhere under this root;
+    /// anything outside it (an absolute path elsewhere, a `../` escape) is
+    /// rejected rather than read. Defaults to the process's working directory.
+    pub workspace_root: PathBuf,
+}
+
+impl Default for IngestOptions {
+    fn default() -> Self {
+        Self {
+            pattern: None,
+            allowed_extensions: None,
+            max_file_size: 10 * 1024 * 1024,
+            concurrency: 5,
+            max_files: None,
+            chunking: None,
+            workspace_root: std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
+        }
+    }
+}
+
+/// Chunking is only requested by tools that expose the knobs for it
+/// (currently `youtu_agent_analyze_files`); `IngestOptions::chunking` stays
+/// `None` for callers that just want whole-file reads.
+#[derive(Debug, Clone)]
+pub struct ChunkOptions {
+    pub max_chunk_size: usize,
+    pub preserve_semantics: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileChunk {
+    pub file: String,
+    pub chunk_index: usize,
+    pub content: String,
+    pub byte_len: usize,
+    pub token_estimate: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileSummary {
+    pub path: String,
+    pub byte_len: usize,
+    pub token_estimate: usize,
+    pub chunk_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IngestResult {
+    pub chunks: Vec<FileChunk>,
+    pub files: Vec<FileSummary>,
+    pub skipped: Vec<String>,
+}
+
+/// Expands `inputs` (bare paths, directories, and glob patterns) into a
+/// concrete, filtered, deduplicated file list, reads every file
+/// concurrently (bounded by `options.concurrency` so a large file set
+/// can't exhaust file descriptors), and - when `options.chunking` is set -
+/// splits each file's contents into token-bounded chunks.
+pub async fn ingest_files(inputs: &[String], options: IngestOptions) -> Result<IngestResult> {
+    let (candidates, mut skipped) = expand_inputs(inputs, options.pattern.as_deref(), &options.workspace_root)?;
+
+    let mut filtered: Vec<PathBuf> = Vec::new();
+    for path in candidates {
+        if let Some(extensions) = &options.allowed_extensions {
+            let matches = path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .map(|ext| extensions.iter().any(|allowed| allowed.trim_start_matches('.').eq_ignore_ascii_case(ext)))
+                .unwrap_or(false);
+
+            if !matches {
+                skipped.push(format!("{}: extension not allowed", path.display()));
+                continue;
+            }
+        }
+        filtered.push(path);
+    }
+
+    if let Some(max_files) = options.max_files {
+        if filtered.len() > max_files {
+            let dropped = filtered.split_off(max_files);
+            for path in dropped {
+                skipped.push(format!("{}: over max_files limit", path.display()));
+            }
+        }
+    }
+
+    let semaphore = Arc::new(Semaphore::new(options.concurrency.max(1)));
+    let reads = filtered.into_iter().map(|path| {
+        let semaphore = semaphore.clone();
+        let max_file_size = options.max_file_size;
+        async move {
+            let _permit = semaphore.acquire().await.expect("semaphore not closed");
+            read_file_bounded(&path, max_file_size).await
+        }
+    });
+
+    let read_results = futures::future::join_all(reads).await;
+
+    let mut chunks = Vec::new();
+    let mut files = Vec::new();
+
+    for result in read_results {
+        match result {
+            Ok(Some((path, content))) => {
+                let byte_len = content.len();
+                let token_estimate = byte_len / CHARS_PER_TOKEN;
+                let file_chunks = match &options.chunking {
+                    Some(chunk_options) => chunk_content(&content, chunk_options),
+                    None => vec![content],
+                };
+                let chunk_count = file_chunks.len();
+                let display_path = path.display().to_string();
+
+                for (chunk_index, chunk) in file_chunks.into_iter().enumerate() {
+                    chunks.push(FileChunk {
+                        file: display_path.clone(),
+                        chunk_index,
+                        byte_len: chunk.len(),
+                        token_estimate: chunk.len() / CHARS_PER_TOKEN,
+                        content: chunk,
+                    });
+                }
+
+                files.push(FileSummary {
+                    path: display_path,
+                    byte_len,
+                    token_estimate,
+                    chunk_count,
+                });
+            }
+            Ok(None) => {}
+            Err(message) => skipped.push(message),
+        }
+    }
+
+    Ok(IngestResult { chunks, files, skipped })
+}
+
+/// Resolves a mix of bare paths, directories and glob patterns into a
+/// deduplicated, sorted list of files. A directory input is combined with
+/// `pattern` (default `*`) so `files: ["src"], pattern: "*.rs"` behaves the
+/// way callers expect. Every candidate is canonicalized and checked against
+/// `workspace_root`; anything that resolves outside it (directly, through a
+/// `../` escape, or via a symlink) is rejected rather than handed back to the
+/// caller, since these inputs come straight from MCP tool arguments.
+fn expand_inputs(inputs: &[String], pattern: Option<&str>, workspace_root: &Path) -> Result<(Vec<PathBuf>, Vec<String>)> {
+    let workspace_root = workspace_root
+        .canonicalize()
+        .map_err(|e| anyhow!("Invalid workspace root '{}': {}", workspace_root.display(), e))?;
+
+    let default_pattern = pattern.unwrap_or("*");
+    let mut resolved = Vec::new();
+    let mut skipped = Vec::new();
+
+    for input in inputs {
+        let path = Path::new(input);
+        let glob_pattern = if path.is_dir() {
+            format!("{}/**/{}", input.trim_end_matches('/'), default_pattern)
+        } else if input.contains('*') || input.contains('?') || input.contains('[') {
+            input.clone()
+        } else {
+            sandbox_candidate(path, &workspace_root, &mut resolved, &mut skipped);
+            continue;
+        };
+
+        let matches = glob::glob(&glob_pattern)
+            .map_err(|e| anyhow!("Invalid file pattern '{}': {}", glob_pattern, e))?;
+
+        for entry in matches {
+            match entry {
+                Ok(found) if found.is_file() => sandbox_candidate(&found, &workspace_root, &mut resolved, &mut skipped),
+                Ok(_) => {}
+                Err(e) => warn!("Skipping unreadable glob entry: {}", e),
+            }
+        }
+    }
+
+    resolved.sort();
+    resolved.dedup();
+    Ok((resolved, skipped))
+}
+
+/// Canonicalizes `path` and, if it falls under `workspace_root`, pushes the
+/// canonical path into `resolved`; otherwise records why it was rejected in
+/// `skipped` instead of letting it reach `read_file_bounded`.
+fn sandbox_candidate(path: &Path, workspace_root: &Path, resolved: &mut Vec<PathBuf>, skipped: &mut Vec<String>) {
+    match path.canonicalize() {
+        Ok(canonical) if canonical.starts_with(workspace_root) => resolved.push(canonical),
+        Ok(_) => skipped.push(format!("{}: outside workspace root, rejected", path.display())),
+        Err(e) => skipped.push(format!("{}: {}", path.display(), e)),
+    }
+}
+
+async fn read_file_bounded(path: &Path, max_file_size: u64) -> Result<Option<(PathBuf, String)>, String> {
+    let metadata = tokio::fs::metadata(path)
+        .await
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    if metadata.len() > max_file_size {
+        return Err(format!(
+            "{}: {} bytes exceeds max_file_size {} bytes",
+            path.display(),
+            metadata.len(),
+            max_file_size
+        ));
+    }
+
+    match tokio::fs::read_to_string(path).await {
+        Ok(content) => Ok(Some((path.to_path_buf(), content))),
+        Err(e) => {
+            debug!("Failed to read {} as UTF-8 text: {}", path.display(), e);
+            Err(format!("{}: {}", path.display(), e))
+        }
+    }
+}
+
+/// Greedily accumulates lines up to `max_chunk_size` tokens per chunk. When
+/// `preserve_semantics` is set, a chunk that would otherwise split mid-block
+/// backs its boundary off to the nearest blank line or top-level
+/// declaration (a line that isn't indented) within the accumulated lines,
+/// carrying the remainder into the next chunk, so functions and blocks
+/// aren't cut in half.
+fn chunk_content(content: &str, options: &ChunkOptions) -> Vec<String> {
+    let char_budget = options.max_chunk_size.saturating_mul(CHARS_PER_TOKEN).max(1);
+
+    if content.len() <= char_budget {
+        return vec![content.to_string()];
+    }
+
+    let lines: Vec<&str> = content.lines().collect();
+    let mut chunks = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    let mut current_len = 0usize;
+
+    for line in lines {
+        let line_len = line.len() + 1;
+
+        if !current.is_empty() && current_len + line_len > char_budget {
+            let split_at = if options.preserve_semantics {
+                find_semantic_split(&current)
+            } else {
+                None
+            };
+
+            match split_at {
+                Some(index) => {
+                    let carry_over = current.split_off(index + 1);
+                    chunks.push(current.join("\n"));
+                    current_len = carry_over.iter().map(|l| l.len() + 1).sum();
+                    current = carry_over;
+                }
+                None => {
+                    chunks.push(std::mem::take(&mut current).join("\n"));
+                    current_len = 0;
+                }
+            }
+        }
+
+        current.push(line);
+        current_len += line_len;
+    }
+
+    if !current.is_empty() {
+        chunks.push(current.join("\n"));
+    }
+
+    chunks
+}
+
+/// Scans backward through the lines accumulated so far for the last blank
+/// line or unindented ("top-level") line, which is taken as a safe split
+/// point. Returns `None` when nothing better than a hard cut is available.
+fn find_semantic_split(lines: &[&str]) -> Option<usize> {
+    (0..lines.len().saturating_sub(1)).rev().find(|&i| {
+        let line = lines[i];
+        line.trim().is_empty() || !line.starts_with(char::is_whitespace)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunk_content_keeps_small_files_whole() {
+        let content = "fn main() {}\n";
+        let chunks = chunk_content(content, &ChunkOptions { max_chunk_size: 1000, preserve_semantics: true });
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0], content);
+    }
+
+    #[test]
+    fn chunk_content_backs_off_to_blank_line() {
+        let content = format!("fn first() {{\n{}\n}}\n\nfn second() {{\n{}\n}}\n", "x".repeat(20), "y".repeat(20));
+        let options = ChunkOptions { max_chunk_size: 10, preserve_semantics: true };
+        let chunks = chunk_content(&content, &options);
+        assert!(chunks.len() >= 2);
+        assert!(chunks[0].trim_end().ends_with('}'));
+    }
+}
@@ -5,7 +5,9 @@ pub mod config;
 pub mod deepseek;
 pub mod mcp;
 pub mod health;
+pub mod ingest;
 pub mod metrics;
+pub mod tcp_info;
 // pub mod server; // Disabled due to axum version incompatibility
 
 // Security modules
@@ -1,28 +1,275 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use dashmap::DashMap;
+use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::time::{SystemTime, UNIX_EPOCH};
-use tracing::debug;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{debug, info, warn};
 
-use crate::config::Config;
+use crate::config::{Config, ConsumptionConfig, OtlpConfig};
+
+/// Ordered label dimensions identifying one metric series (endpoint, status
+/// class, upstream model, ...). Backed by a `BTreeMap` so its derived
+/// `Hash`/`Eq` depend only on the label set itself, not the order `with()`
+/// calls were chained in - two callers describing the same series always key
+/// identically in the underlying `DashMap`s.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MetricLabels(std::collections::BTreeMap<String, String>);
+
+impl MetricLabels {
+    /// Start a label set with the (mandatory) `endpoint` dimension.
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        Self::default().with("endpoint", endpoint)
+    }
+
+    /// Add or overwrite a label dimension, consuming and returning `self` for
+    /// chaining, e.g. `MetricLabels::new("tools/call").with("status", "200")`.
+    pub fn with(mut self, key: &str, value: impl Into<String>) -> Self {
+        self.0.insert(key.to_string(), value.into());
+        self
+    }
+
+    pub fn endpoint(&self) -> &str {
+        self.0.get("endpoint").map(|v| v.as_str()).unwrap_or("unknown")
+    }
+
+    fn pairs(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// Stable, collision-free string form of the label set (e.g.
+    /// `endpoint=tools/call,status=200`), used anywhere a single string key
+    /// is needed (on-disk caches, billing events) instead of the full map.
+    fn canonical_string(&self) -> String {
+        self.pairs().map(|(k, v)| format!("{}={}", k, v)).collect::<Vec<_>>().join(",")
+    }
+
+    /// Render as a Prometheus label block (`{k="v",k2="v2"}`), escaping
+    /// backslashes, quotes, and newlines per the text exposition format.
+    pub fn to_prometheus(&self) -> String {
+        self.to_prometheus_with(&[])
+    }
+
+    /// Same as `to_prometheus`, with extra `(key, value)` pairs appended -
+    /// for labels that only apply to one exported metric (e.g. a histogram
+    /// bucket's `le`) rather than the whole series.
+    pub fn to_prometheus_with(&self, extra: &[(&str, &str)]) -> String {
+        let body = self.pairs()
+            .chain(extra.iter().copied())
+            .map(|(k, v)| format!("{}=\"{}\"", k, escape_prometheus_label_value(v)))
+            .collect::<Vec<_>>()
+            .join(",");
+        format!("{{{}}}", body)
+    }
+}
+
+fn escape_prometheus_label_value(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
 
 pub struct MetricsCollector {
     config: Arc<Config>,
-    request_counts: DashMap<String, AtomicU64>,
-    success_counts: DashMap<String, AtomicU64>,
-    error_counts: DashMap<String, AtomicU64>,
-    response_times: DashMap<String, ResponseTimeMetrics>,
+    // Shared with the OTLP export task (when enabled), so both it and the
+    // collector itself read/write the same counters.
+    request_counts: Arc<DashMap<MetricLabels, AtomicU64>>,
+    success_counts: Arc<DashMap<MetricLabels, AtomicU64>>,
+    error_counts: Arc<DashMap<MetricLabels, AtomicU64>>,
+    response_times: Arc<DashMap<MetricLabels, ResponseTimeMetrics>>,
+    // Per-series rolling 1-minute window, for recent throughput/error rate
+    // rather than only lifetime totals.
+    rate_windows: Arc<DashMap<MetricLabels, RateWindow>>,
+    // Most recent `TCP_INFO` sample from the HTTP transport's listener, when
+    // `server.tcp.collect_tcp_info` is enabled. Not per-connection - just the
+    // latest sample across whichever connection was last polled - since this
+    // is meant as a coarse upstream-health signal, not per-client accounting.
+    tcp_info: TcpInfoMetrics,
     start_time: u64,
 }
 
+#[derive(Debug, Default)]
+struct TcpInfoMetrics {
+    last_rtt_us: AtomicU64,
+    last_retransmits: AtomicU64,
+    samples_total: AtomicU64,
+}
+
+/// Width of the sliding rate-metrics window, in one-second slots.
+const RATE_WINDOW_SECONDS: usize = 60;
+
+fn now_unix_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
+#[derive(Debug, Default)]
+struct RateWindowState {
+    request_slots: [u64; RATE_WINDOW_SECONDS],
+    error_slots: [u64; RATE_WINDOW_SECONDS],
+    last_slot_second: u64,
+}
+
+/// Ring buffer of `RATE_WINDOW_SECONDS` one-second slots tracking recent
+/// request/error counts, so `requests_per_sec_1m`/`errors_per_sec_1m` reflect
+/// current traffic instead of a lifetime average that never moves. Expired
+/// slots are zeroed lazily, based on wall-clock second index, on every read
+/// or write rather than via a background sweep.
+#[derive(Debug, Default)]
+struct RateWindow {
+    state: std::sync::Mutex<RateWindowState>,
+}
+
+impl RateWindow {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    fn advance(state: &mut RateWindowState, now_sec: u64) {
+        if state.last_slot_second == 0 {
+            state.last_slot_second = now_sec;
+            return;
+        }
+        let elapsed = now_sec.saturating_sub(state.last_slot_second);
+        if elapsed == 0 {
+            return;
+        }
+        if elapsed as usize >= RATE_WINDOW_SECONDS {
+            state.request_slots = [0; RATE_WINDOW_SECONDS];
+            state.error_slots = [0; RATE_WINDOW_SECONDS];
+        } else {
+            for offset in 1..=elapsed {
+                let idx = ((state.last_slot_second + offset) as usize) % RATE_WINDOW_SECONDS;
+                state.request_slots[idx] = 0;
+                state.error_slots[idx] = 0;
+            }
+        }
+        state.last_slot_second = now_sec;
+    }
+
+    fn record_request(&self, now_sec: u64) {
+        let mut state = self.state.lock().unwrap();
+        Self::advance(&mut state, now_sec);
+        let idx = (now_sec as usize) % RATE_WINDOW_SECONDS;
+        state.request_slots[idx] += 1;
+    }
+
+    fn record_error(&self, now_sec: u64) {
+        let mut state = self.state.lock().unwrap();
+        Self::advance(&mut state, now_sec);
+        let idx = (now_sec as usize) % RATE_WINDOW_SECONDS;
+        state.error_slots[idx] += 1;
+    }
+
+    /// Sum of live slots, as `(request_count, error_count)` over the window.
+    fn totals(&self, now_sec: u64) -> (u64, u64) {
+        let mut state = self.state.lock().unwrap();
+        Self::advance(&mut state, now_sec);
+        (state.request_slots.iter().sum(), state.error_slots.iter().sum())
+    }
+}
+
+/// Bits of linear resolution within each power-of-two "group" of the
+/// response-time histogram. 3 bits gives 8 sub-buckets per doubling, i.e.
+/// ~12.5% relative error - enough for p50/p90/p99 dashboards without storing
+/// per-request samples.
+const HISTOGRAM_PRECISION_BITS: u32 = 3;
+const HISTOGRAM_SUB_BUCKETS: usize = 1 << HISTOGRAM_PRECISION_BITS;
+/// Magnitude groups above the direct (sub-precision) range. 48 groups covers
+/// response times up to roughly 2^50 ms, far beyond anything this bridge will
+/// ever record; values beyond that saturate into the last bucket.
+const HISTOGRAM_GROUPS: usize = 48;
+const HISTOGRAM_BUCKET_COUNT: usize = HISTOGRAM_SUB_BUCKETS * HISTOGRAM_GROUPS;
+
+/// Map a response time to its histogram bucket. Values below
+/// `HISTOGRAM_SUB_BUCKETS` map directly (group 0 is linear); larger values are
+/// split into a magnitude group (`floor(log2(value))`) and a linear index
+/// within that group's `HISTOGRAM_SUB_BUCKETS` sub-buckets, so resolution
+/// scales with magnitude instead of being wasted on the long tail.
+fn histogram_bucket_index(value: u64) -> usize {
+    if value == 0 {
+        return 0;
+    }
+    let msb = 63 - value.leading_zeros();
+    if (msb as usize) < HISTOGRAM_PRECISION_BITS as usize {
+        return value as usize;
+    }
+    let group = (msb - HISTOGRAM_PRECISION_BITS + 1) as usize;
+    if group >= HISTOGRAM_GROUPS {
+        return HISTOGRAM_BUCKET_COUNT - 1;
+    }
+    let shift = msb - HISTOGRAM_PRECISION_BITS;
+    let sub_index = ((value >> shift) as usize) & (HISTOGRAM_SUB_BUCKETS - 1);
+    group * HISTOGRAM_SUB_BUCKETS + sub_index
+}
+
+/// Inverse of `histogram_bucket_index`: the representative (lower-bound) value
+/// of a bucket, used as the reported value for a percentile that falls in it.
+fn histogram_bucket_lower_bound(index: usize) -> u64 {
+    if index < HISTOGRAM_SUB_BUCKETS {
+        return index as u64;
+    }
+    let group = index / HISTOGRAM_SUB_BUCKETS;
+    let sub_index = index % HISTOGRAM_SUB_BUCKETS;
+    let shift = (group - 1) as u32;
+    ((HISTOGRAM_SUB_BUCKETS + sub_index) as u64) << shift
+}
+
+/// Upper bound of a bucket (one less than the next bucket's lower bound), used
+/// as the `le` value when exporting buckets in Prometheus histogram format.
+fn histogram_bucket_upper_bound(index: usize) -> u64 {
+    if index + 1 < HISTOGRAM_BUCKET_COUNT {
+        histogram_bucket_lower_bound(index + 1).saturating_sub(1)
+    } else {
+        u64::MAX
+    }
+}
+
+/// Estimate the `q`-th percentile (0.0-1.0) from a bucket-count snapshot,
+/// walking buckets in order until the cumulative count crosses `ceil(N * q)`.
+fn histogram_percentile(buckets: &[u64], q: f64) -> u64 {
+    let total: u64 = buckets.iter().sum();
+    if total == 0 {
+        return 0;
+    }
+    let target = (total as f64 * q).ceil() as u64;
+    let mut cumulative = 0u64;
+    for (index, count) in buckets.iter().enumerate() {
+        cumulative += count;
+        if cumulative >= target {
+            return histogram_bucket_lower_bound(index);
+        }
+    }
+    histogram_bucket_lower_bound(buckets.len() - 1)
+}
+
 #[derive(Debug)]
 struct ResponseTimeMetrics {
     total_ms: AtomicU64,
     count: AtomicU64,
     min_ms: AtomicU64,
     max_ms: AtomicU64,
+    // Lock-free HDR-style histogram for percentile estimation; element-wise
+    // addition across endpoints gives a correct merged histogram if ever needed.
+    histogram: Box<[AtomicU64]>,
+}
+
+impl ResponseTimeMetrics {
+    fn new() -> Self {
+        Self {
+            total_ms: AtomicU64::new(0),
+            count: AtomicU64::new(0),
+            min_ms: AtomicU64::new(u64::MAX),
+            max_ms: AtomicU64::new(0),
+            histogram: (0..HISTOGRAM_BUCKET_COUNT).map(|_| AtomicU64::new(0)).collect(),
+        }
+    }
+
+    /// Snapshot every bucket's count so multiple percentiles can be computed
+    /// from one consistent view instead of re-reading the atomics per call.
+    fn histogram_snapshot(&self) -> Vec<u64> {
+        self.histogram.iter().map(|bucket| bucket.load(Ordering::Relaxed)).collect()
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -32,6 +279,17 @@ pub struct MetricsSnapshot {
     pub version: String,
     pub requests: MetricsSummary,
     pub endpoints: Vec<EndpointMetrics>,
+    /// Most recent `TCP_INFO` sample off the HTTP transport's listener, or
+    /// `None` when `server.tcp.collect_tcp_info` is off (or no sample has
+    /// been taken yet).
+    pub tcp: Option<TcpHealthMetrics>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TcpHealthMetrics {
+    pub rtt_us: u64,
+    pub retransmits: u64,
+    pub samples_total: u64,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -45,7 +303,7 @@ pub struct MetricsSummary {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct EndpointMetrics {
-    pub name: String,
+    pub labels: MetricLabels,
     pub request_count: u64,
     pub success_count: u64,
     pub error_count: u64,
@@ -53,6 +311,13 @@ pub struct EndpointMetrics {
     pub avg_response_time_ms: f64,
     pub min_response_time_ms: u64,
     pub max_response_time_ms: u64,
+    pub p50_ms: u64,
+    pub p90_ms: u64,
+    pub p99_ms: u64,
+    pub p999_ms: u64,
+    pub requests_per_sec_1m: f64,
+    pub errors_per_sec_1m: f64,
+    pub error_rate_1m: f64,
 }
 
 impl MetricsCollector {
@@ -62,71 +327,140 @@ impl MetricsCollector {
             .unwrap_or_default()
             .as_secs();
 
+        let request_counts = Arc::new(DashMap::new());
+        let success_counts = Arc::new(DashMap::new());
+        let error_counts = Arc::new(DashMap::new());
+        let response_times = Arc::new(DashMap::new());
+        let rate_windows = Arc::new(DashMap::new());
+
+        if config.metrics.otlp.enabled {
+            let otlp = config.metrics.otlp.clone();
+            let request_counts = request_counts.clone();
+            let success_counts = success_counts.clone();
+            let error_counts = error_counts.clone();
+            let response_times = response_times.clone();
+
+            tokio::spawn(async move {
+                let client = Client::new();
+                let mut ticker = tokio::time::interval(Duration::from_secs(otlp.interval_seconds.max(1)));
+                loop {
+                    ticker.tick().await;
+                    if let Err(e) = push_otlp_metrics(
+                        &client,
+                        &otlp,
+                        &request_counts,
+                        &success_counts,
+                        &error_counts,
+                        &response_times,
+                    )
+                    .await
+                    {
+                        warn!("OTLP metrics export failed: {}", e);
+                    }
+                }
+            });
+
+            info!(
+                "OTLP metrics export enabled: pushing to {} every {}s",
+                config.metrics.otlp.endpoint, config.metrics.otlp.interval_seconds
+            );
+        }
+
+        if config.metrics.consumption.enabled {
+            let consumption = config.metrics.consumption.clone();
+            let request_counts = request_counts.clone();
+            let error_counts = error_counts.clone();
+
+            tokio::spawn(async move {
+                let client = Client::new();
+                let mut ticker = tokio::time::interval(Duration::from_secs(consumption.interval_seconds.max(1)));
+                loop {
+                    ticker.tick().await;
+                    if let Err(e) = upload_consumption_metrics(&client, &consumption, &request_counts, &error_counts).await {
+                        warn!("Consumption-metrics upload failed: {}", e);
+                    }
+                }
+            });
+
+            info!(
+                "Consumption-metrics upload enabled: pushing usage deltas to {} every {}s",
+                config.metrics.consumption.endpoint, config.metrics.consumption.interval_seconds
+            );
+        }
+
         Self {
             config,
-            request_counts: DashMap::new(),
-            success_counts: DashMap::new(),
-            error_counts: DashMap::new(),
-            response_times: DashMap::new(),
+            request_counts,
+            success_counts,
+            error_counts,
+            response_times,
+            rate_windows,
+            tcp_info: TcpInfoMetrics::default(),
             start_time,
         }
     }
 
-    pub async fn increment_request_count(&self, endpoint: &str) {
+    pub async fn increment_request_count(&self, labels: MetricLabels) {
         if !self.config.metrics.enabled {
             return;
         }
 
         self.request_counts
-            .entry(endpoint.to_string())
+            .entry(labels.clone())
             .or_insert_with(|| AtomicU64::new(0))
             .fetch_add(1, Ordering::Relaxed);
 
-        debug!("Request count incremented for endpoint: {}", endpoint);
+        self.rate_windows
+            .entry(labels.clone())
+            .or_insert_with(RateWindow::new)
+            .record_request(now_unix_secs());
+
+        debug!("Request count incremented for {:?}", labels);
     }
 
-    pub async fn increment_success_count(&self, endpoint: &str) {
+    pub async fn increment_success_count(&self, labels: MetricLabels) {
         if !self.config.metrics.enabled {
             return;
         }
 
         self.success_counts
-            .entry(endpoint.to_string())
+            .entry(labels.clone())
             .or_insert_with(|| AtomicU64::new(0))
             .fetch_add(1, Ordering::Relaxed);
 
-        debug!("Success count incremented for endpoint: {}", endpoint);
+        debug!("Success count incremented for {:?}", labels);
     }
 
-    pub async fn increment_error_count(&self, endpoint: &str) {
+    pub async fn increment_error_count(&self, labels: MetricLabels) {
         if !self.config.metrics.enabled {
             return;
         }
 
         self.error_counts
-            .entry(endpoint.to_string())
+            .entry(labels.clone())
             .or_insert_with(|| AtomicU64::new(0))
             .fetch_add(1, Ordering::Relaxed);
 
-        debug!("Error count incremented for endpoint: {}", endpoint);
+        self.rate_windows
+            .entry(labels.clone())
+            .or_insert_with(RateWindow::new)
+            .record_error(now_unix_secs());
+
+        debug!("Error count incremented for {:?}", labels);
     }
 
-    pub async fn record_response_time(&self, endpoint: &str, duration_ms: u64) {
+    pub async fn record_response_time(&self, labels: MetricLabels, duration_ms: u64) {
         if !self.config.metrics.enabled {
             return;
         }
 
         let metrics = self.response_times
-            .entry(endpoint.to_string())
-            .or_insert_with(|| ResponseTimeMetrics {
-                total_ms: AtomicU64::new(0),
-                count: AtomicU64::new(0),
-                min_ms: AtomicU64::new(u64::MAX),
-                max_ms: AtomicU64::new(0),
-            });
+            .entry(labels.clone())
+            .or_insert_with(ResponseTimeMetrics::new);
 
         metrics.total_ms.fetch_add(duration_ms, Ordering::Relaxed);
         metrics.count.fetch_add(1, Ordering::Relaxed);
+        metrics.histogram[histogram_bucket_index(duration_ms)].fetch_add(1, Ordering::Relaxed);
 
         // Update min
         let mut current_min = metrics.min_ms.load(Ordering::Relaxed);
@@ -156,7 +490,46 @@ impl MetricsCollector {
             }
         }
 
-        debug!("Response time recorded for endpoint {}: {}ms", endpoint, duration_ms);
+        debug!("Response time recorded for {:?}: {}ms", labels, duration_ms);
+    }
+
+    /// Records the latest `TCP_INFO` sample (rtt, cumulative retransmits)
+    /// taken off an accepted HTTP transport connection, when
+    /// `server.tcp.collect_tcp_info` is enabled. Only the most recent sample
+    /// is kept - this is a coarse upstream-health gauge, not per-connection
+    /// accounting.
+    pub async fn record_tcp_info(&self, rtt_us: u64, retransmits: u64) {
+        if !self.config.metrics.enabled {
+            return;
+        }
+
+        self.tcp_info.last_rtt_us.store(rtt_us, Ordering::Relaxed);
+        self.tcp_info.last_retransmits.store(retransmits, Ordering::Relaxed);
+        self.tcp_info.samples_total.fetch_add(1, Ordering::Relaxed);
+
+        debug!("TCP_INFO sample recorded: rtt={}us retransmits={}", rtt_us, retransmits);
+    }
+
+    /// Current cumulative `(request_count, error_count)` per label set, for
+    /// consumers (e.g. the consumption uploader) that need raw counters
+    /// rather than a full formatted snapshot.
+    pub async fn raw_counts_snapshot(&self) -> Vec<(MetricLabels, u64, u64)> {
+        let mut endpoint_names: std::collections::HashSet<MetricLabels> = std::collections::HashSet::new();
+        for entry in self.request_counts.iter() {
+            endpoint_names.insert(entry.key().clone());
+        }
+        for entry in self.error_counts.iter() {
+            endpoint_names.insert(entry.key().clone());
+        }
+
+        endpoint_names
+            .into_iter()
+            .map(|endpoint| {
+                let request_count = self.request_counts.get(&endpoint).map(|v| v.load(Ordering::Relaxed)).unwrap_or(0);
+                let error_count = self.error_counts.get(&endpoint).map(|v| v.load(Ordering::Relaxed)).unwrap_or(0);
+                (endpoint, request_count, error_count)
+            })
+            .collect()
     }
 
     pub async fn export(&self) -> Result<String> {
@@ -186,9 +559,9 @@ impl MetricsCollector {
         let mut total_errors = 0u64;
         let mut endpoints = Vec::new();
 
-        // Collect all unique endpoint names
-        let mut endpoint_names: std::collections::HashSet<String> = std::collections::HashSet::new();
-        
+        // Collect all unique label sets, i.e. distinct metric series
+        let mut endpoint_names: std::collections::HashSet<MetricLabels> = std::collections::HashSet::new();
+
         for entry in self.request_counts.iter() {
             endpoint_names.insert(entry.key().clone());
         }
@@ -221,7 +594,7 @@ impl MetricsCollector {
                 0.0
             };
 
-            let (avg_response_time_ms, min_response_time_ms, max_response_time_ms) = 
+            let (avg_response_time_ms, min_response_time_ms, max_response_time_ms, p50_ms, p90_ms, p99_ms, p999_ms) =
                 if let Some(metrics) = self.response_times.get(&endpoint) {
                     let total = metrics.total_ms.load(Ordering::Relaxed);
                     let count = metrics.count.load(Ordering::Relaxed);
@@ -234,13 +607,34 @@ impl MetricsCollector {
                         0.0
                     };
 
-                    (avg, if min == u64::MAX { 0 } else { min }, max)
+                    let buckets = metrics.histogram_snapshot();
+                    (
+                        avg,
+                        if min == u64::MAX { 0 } else { min },
+                        max,
+                        histogram_percentile(&buckets, 0.50),
+                        histogram_percentile(&buckets, 0.90),
+                        histogram_percentile(&buckets, 0.99),
+                        histogram_percentile(&buckets, 0.999),
+                    )
                 } else {
-                    (0.0, 0, 0)
+                    (0.0, 0, 0, 0, 0, 0, 0)
                 };
 
+            let (window_requests, window_errors) = self.rate_windows
+                .get(&endpoint)
+                .map(|w| w.totals(now))
+                .unwrap_or((0, 0));
+            let requests_per_sec_1m = window_requests as f64 / RATE_WINDOW_SECONDS as f64;
+            let errors_per_sec_1m = window_errors as f64 / RATE_WINDOW_SECONDS as f64;
+            let error_rate_1m = if window_requests > 0 {
+                (window_errors as f64 / window_requests as f64) * 100.0
+            } else {
+                0.0
+            };
+
             endpoints.push(EndpointMetrics {
-                name: endpoint,
+                labels: endpoint,
                 request_count,
                 success_count,
                 error_count,
@@ -248,6 +642,13 @@ impl MetricsCollector {
                 avg_response_time_ms,
                 min_response_time_ms,
                 max_response_time_ms,
+                p50_ms,
+                p90_ms,
+                p99_ms,
+                p999_ms,
+                requests_per_sec_1m,
+                errors_per_sec_1m,
+                error_rate_1m,
             });
 
             total_requests += request_count;
@@ -267,6 +668,17 @@ impl MetricsCollector {
             0.0
         };
 
+        let samples_total = self.tcp_info.samples_total.load(Ordering::Relaxed);
+        let tcp = if samples_total > 0 {
+            Some(TcpHealthMetrics {
+                rtt_us: self.tcp_info.last_rtt_us.load(Ordering::Relaxed),
+                retransmits: self.tcp_info.last_retransmits.load(Ordering::Relaxed),
+                samples_total,
+            })
+        } else {
+            None
+        };
+
         MetricsSnapshot {
             timestamp: now,
             uptime_seconds,
@@ -279,6 +691,7 @@ impl MetricsCollector {
                 error_rate,
             },
             endpoints,
+            tcp,
         }
     }
 
@@ -289,8 +702,8 @@ impl MetricsCollector {
         output.push_str("# TYPE deepseek_mcp_requests_total counter\n");
         for endpoint in &snapshot.endpoints {
             output.push_str(&format!(
-                "deepseek_mcp_requests_total{{endpoint=\"{}\"}} {}\n",
-                endpoint.name, endpoint.request_count
+                "deepseek_mcp_requests_total{} {}\n",
+                endpoint.labels.to_prometheus(), endpoint.request_count
             ));
         }
 
@@ -298,8 +711,8 @@ impl MetricsCollector {
         output.push_str("# TYPE deepseek_mcp_successes_total counter\n");
         for endpoint in &snapshot.endpoints {
             output.push_str(&format!(
-                "deepseek_mcp_successes_total{{endpoint=\"{}\"}} {}\n",
-                endpoint.name, endpoint.success_count
+                "deepseek_mcp_successes_total{} {}\n",
+                endpoint.labels.to_prometheus(), endpoint.success_count
             ));
         }
 
@@ -307,8 +720,8 @@ impl MetricsCollector {
         output.push_str("# TYPE deepseek_mcp_errors_total counter\n");
         for endpoint in &snapshot.endpoints {
             output.push_str(&format!(
-                "deepseek_mcp_errors_total{{endpoint=\"{}\"}} {}\n",
-                endpoint.name, endpoint.error_count
+                "deepseek_mcp_errors_total{} {}\n",
+                endpoint.labels.to_prometheus(), endpoint.error_count
             ));
         }
 
@@ -316,8 +729,70 @@ impl MetricsCollector {
         output.push_str("# TYPE deepseek_mcp_response_time_ms gauge\n");
         for endpoint in &snapshot.endpoints {
             output.push_str(&format!(
-                "deepseek_mcp_response_time_ms{{endpoint=\"{}\"}} {:.2}\n",
-                endpoint.name, endpoint.avg_response_time_ms
+                "deepseek_mcp_response_time_ms{} {:.2}\n",
+                endpoint.labels.to_prometheus(), endpoint.avg_response_time_ms
+            ));
+        }
+
+        output.push_str("\n# HELP deepseek_mcp_response_time_ms_histogram Response time distribution in milliseconds\n");
+        output.push_str("# TYPE deepseek_mcp_response_time_ms_histogram histogram\n");
+        for endpoint in &snapshot.endpoints {
+            if let Some(metrics) = self.response_times.get(&endpoint.labels) {
+                let buckets = metrics.histogram_snapshot();
+                let mut cumulative = 0u64;
+                for (index, count) in buckets.iter().enumerate() {
+                    if *count == 0 && cumulative == 0 {
+                        continue;
+                    }
+                    cumulative += count;
+                    let le = histogram_bucket_upper_bound(index).to_string();
+                    output.push_str(&format!(
+                        "deepseek_mcp_response_time_ms_histogram_bucket{} {}\n",
+                        endpoint.labels.to_prometheus_with(&[("le", &le)]),
+                        cumulative
+                    ));
+                }
+                output.push_str(&format!(
+                    "deepseek_mcp_response_time_ms_histogram_bucket{} {}\n",
+                    endpoint.labels.to_prometheus_with(&[("le", "+Inf")]), cumulative
+                ));
+                output.push_str(&format!(
+                    "deepseek_mcp_response_time_ms_histogram_sum{} {}\n",
+                    endpoint.labels.to_prometheus(),
+                    metrics.total_ms.load(Ordering::Relaxed)
+                ));
+                output.push_str(&format!(
+                    "deepseek_mcp_response_time_ms_histogram_count{} {}\n",
+                    endpoint.labels.to_prometheus(),
+                    metrics.count.load(Ordering::Relaxed)
+                ));
+            }
+        }
+
+        output.push_str("\n# HELP deepseek_mcp_requests_per_sec_1m Requests per second averaged over the trailing 1-minute window\n");
+        output.push_str("# TYPE deepseek_mcp_requests_per_sec_1m gauge\n");
+        for endpoint in &snapshot.endpoints {
+            output.push_str(&format!(
+                "deepseek_mcp_requests_per_sec_1m{} {:.4}\n",
+                endpoint.labels.to_prometheus(), endpoint.requests_per_sec_1m
+            ));
+        }
+
+        output.push_str("\n# HELP deepseek_mcp_errors_per_sec_1m Errors per second averaged over the trailing 1-minute window\n");
+        output.push_str("# TYPE deepseek_mcp_errors_per_sec_1m gauge\n");
+        for endpoint in &snapshot.endpoints {
+            output.push_str(&format!(
+                "deepseek_mcp_errors_per_sec_1m{} {:.4}\n",
+                endpoint.labels.to_prometheus(), endpoint.errors_per_sec_1m
+            ));
+        }
+
+        output.push_str("\n# HELP deepseek_mcp_error_rate_1m_percent Error rate over the trailing 1-minute window, as a percentage\n");
+        output.push_str("# TYPE deepseek_mcp_error_rate_1m_percent gauge\n");
+        for endpoint in &snapshot.endpoints {
+            output.push_str(&format!(
+                "deepseek_mcp_error_rate_1m_percent{} {:.2}\n",
+                endpoint.labels.to_prometheus(), endpoint.error_rate_1m
             ));
         }
 
@@ -325,6 +800,375 @@ impl MetricsCollector {
         output.push_str("# TYPE deepseek_mcp_uptime_seconds gauge\n");
         output.push_str(&format!("deepseek_mcp_uptime_seconds {}\n", snapshot.uptime_seconds));
 
+        if let Some(tcp) = &snapshot.tcp {
+            output.push_str("\n# HELP deepseek_mcp_tcp_rtt_microseconds Most recent sampled TCP_INFO round-trip time for an HTTP transport connection\n");
+            output.push_str("# TYPE deepseek_mcp_tcp_rtt_microseconds gauge\n");
+            output.push_str(&format!("deepseek_mcp_tcp_rtt_microseconds {}\n", tcp.rtt_us));
+
+            output.push_str("\n# HELP deepseek_mcp_tcp_retransmits_total Most recent sampled cumulative TCP retransmit count for an HTTP transport connection\n");
+            output.push_str("# TYPE deepseek_mcp_tcp_retransmits_total gauge\n");
+            output.push_str(&format!("deepseek_mcp_tcp_retransmits_total {}\n", tcp.retransmits));
+        }
+
         Ok(output)
     }
+}
+
+/// Push one OTLP/HTTP `ExportMetricsServiceRequest` covering every endpoint's
+/// current counters to `otlp.endpoint`. Counts are cumulative sums (as OTLP's
+/// `AGGREGATION_TEMPORALITY_CUMULATIVE = 2` expects); the collector side is
+/// responsible for rate conversion.
+async fn push_otlp_metrics(
+    client: &Client,
+    otlp: &OtlpConfig,
+    request_counts: &DashMap<MetricLabels, AtomicU64>,
+    success_counts: &DashMap<MetricLabels, AtomicU64>,
+    error_counts: &DashMap<MetricLabels, AtomicU64>,
+    response_times: &DashMap<MetricLabels, ResponseTimeMetrics>,
+) -> Result<()> {
+    let time_unix_nano = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos() as u64;
+
+    let mut endpoint_names: std::collections::HashSet<MetricLabels> = std::collections::HashSet::new();
+    for entry in request_counts.iter() {
+        endpoint_names.insert(entry.key().clone());
+    }
+    for entry in success_counts.iter() {
+        endpoint_names.insert(entry.key().clone());
+    }
+    for entry in error_counts.iter() {
+        endpoint_names.insert(entry.key().clone());
+    }
+
+    let mut metrics = Vec::new();
+    for endpoint in &endpoint_names {
+        let count_for = |map: &DashMap<MetricLabels, AtomicU64>| {
+            map.get(endpoint).map(|v| v.load(Ordering::Relaxed)).unwrap_or(0)
+        };
+
+        metrics.push(otlp_sum_metric("deepseek_mcp_requests_total", count_for(request_counts), endpoint, time_unix_nano));
+        metrics.push(otlp_sum_metric("deepseek_mcp_successes_total", count_for(success_counts), endpoint, time_unix_nano));
+        metrics.push(otlp_sum_metric("deepseek_mcp_errors_total", count_for(error_counts), endpoint, time_unix_nano));
+
+        if let Some(rt) = response_times.get(endpoint) {
+            let total = rt.total_ms.load(Ordering::Relaxed);
+            let count = rt.count.load(Ordering::Relaxed);
+            let avg = if count > 0 { total as f64 / count as f64 } else { 0.0 };
+            metrics.push(otlp_gauge_metric("deepseek_mcp_response_time_ms", avg, endpoint, time_unix_nano));
+        }
+    }
+
+    let payload = serde_json::json!({
+        "resourceMetrics": [{
+            "resource": {
+                "attributes": [
+                    {"key": "service.name", "value": {"stringValue": otlp.service_name}},
+                    {"key": "service.version", "value": {"stringValue": otlp.service_version}},
+                ]
+            },
+            "scopeMetrics": [{
+                "scope": {"name": "deepseek-mcp-bridge"},
+                "metrics": metrics,
+            }]
+        }]
+    });
+
+    client
+        .post(format!("{}/v1/metrics", otlp.endpoint.trim_end_matches('/')))
+        .json(&payload)
+        .send()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
+
+fn otlp_attributes(labels: &MetricLabels) -> Vec<serde_json::Value> {
+    labels
+        .pairs()
+        .map(|(k, v)| serde_json::json!({"key": k, "value": {"stringValue": v}}))
+        .collect()
+}
+
+fn otlp_sum_metric(name: &str, value: u64, labels: &MetricLabels, time_unix_nano: u64) -> serde_json::Value {
+    serde_json::json!({
+        "name": name,
+        "sum": {
+            "dataPoints": [{
+                "attributes": otlp_attributes(labels),
+                "timeUnixNano": time_unix_nano.to_string(),
+                "asInt": value.to_string(),
+            }],
+            "aggregationTemporality": 2,
+            "isMonotonic": true
+        }
+    })
+}
+
+fn otlp_gauge_metric(name: &str, value: f64, labels: &MetricLabels, time_unix_nano: u64) -> serde_json::Value {
+    serde_json::json!({
+        "name": name,
+        "gauge": {
+            "dataPoints": [{
+                "attributes": otlp_attributes(labels),
+                "timeUnixNano": time_unix_nano.to_string(),
+                "asDouble": value
+            }]
+        }
+    })
+}
+
+/// One usage event for a single endpoint over `[window_start, window_end)`.
+/// `request_count`/`error_count` are deltas since the last successful upload,
+/// not cumulative totals, so downstream billing can sum them directly.
+/// `idempotency_key` is a deterministic hash of `(endpoint, window_start,
+/// node_id)` so a retried upload of the same window is safely deduped.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ConsumptionEvent {
+    endpoint: String,
+    request_count: u64,
+    error_count: u64,
+    window_start: u64,
+    window_end: u64,
+    idempotency_key: String,
+}
+
+/// On-disk resume state: the cumulative counts observed as of the last
+/// successful upload (the baseline future deltas are computed against), plus
+/// any chunk that was built but never confirmed delivered.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ConsumptionUploadCache {
+    baseline: HashMap<String, (u64, u64)>,
+    last_window_end: u64,
+    pending_chunks: Vec<Vec<ConsumptionEvent>>,
+}
+
+/// Deterministic hash of `(endpoint, window_start, node_id)`, so the receiver
+/// can dedupe an upload retried after a crash or network failure.
+fn consumption_idempotency_key(endpoint: &str, window_start: u64, node_id: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(endpoint.as_bytes());
+    hasher.update(b":");
+    hasher.update(window_start.to_string().as_bytes());
+    hasher.update(b":");
+    hasher.update(node_id.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+fn load_consumption_cache(cache_path: &std::path::Path) -> ConsumptionUploadCache {
+    match std::fs::read_to_string(cache_path) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
+        Err(_) => ConsumptionUploadCache::default(),
+    }
+}
+
+fn save_consumption_cache(cache_path: &std::path::Path, cache: &ConsumptionUploadCache) -> Result<()> {
+    let content = serde_json::to_string(cache)?;
+    if let Some(parent) = cache_path.parent() {
+        std::fs::create_dir_all(parent).ok();
+    }
+    std::fs::write(cache_path, content).context("writing consumption-metrics cache")
+}
+
+/// Snapshot current request/error counters, compute the delta against the
+/// cached baseline, chunk into fixed-size batches, and POST each chunk to the
+/// billing endpoint. Chunks that fail to upload are persisted as pending so
+/// the next interval (or a post-crash restart) retries them before building
+/// any new events, guaranteeing no window is skipped.
+async fn upload_consumption_metrics(
+    client: &Client,
+    consumption: &ConsumptionConfig,
+    request_counts: &DashMap<MetricLabels, AtomicU64>,
+    error_counts: &DashMap<MetricLabels, AtomicU64>,
+) -> Result<()> {
+    let mut cache = load_consumption_cache(&consumption.cache_path);
+    let mut pending = std::mem::take(&mut cache.pending_chunks);
+    if !pending.is_empty() {
+        info!("Resuming {} un-acked consumption-metrics chunk(s)", pending.len());
+    }
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    let window_start = if cache.last_window_end > 0 { cache.last_window_end } else { now };
+
+    let mut new_baseline = cache.baseline.clone();
+    let mut events = Vec::new();
+    for entry in request_counts.iter() {
+        // The full label set, not just the endpoint dimension, identifies the
+        // series - two series sharing an endpoint but differing in e.g.
+        // status code must not be collapsed into one billing event.
+        let endpoint = entry.key().canonical_string();
+        let request_count = entry.value().load(Ordering::Relaxed);
+        let error_count = error_counts.get(entry.key()).map(|v| v.load(Ordering::Relaxed)).unwrap_or(0);
+
+        let (baseline_req, baseline_err) = cache.baseline.get(&endpoint).copied().unwrap_or((0, 0));
+        let delta_req = request_count.saturating_sub(baseline_req);
+        let delta_err = error_count.saturating_sub(baseline_err);
+        new_baseline.insert(endpoint.clone(), (request_count, error_count));
+
+        if delta_req == 0 && delta_err == 0 {
+            continue;
+        }
+
+        events.push(ConsumptionEvent {
+            idempotency_key: consumption_idempotency_key(&endpoint, window_start, &consumption.node_id),
+            endpoint,
+            request_count: delta_req,
+            error_count: delta_err,
+            window_start,
+            window_end: now,
+        });
+    }
+
+    for chunk in events.chunks(consumption.chunk_size.max(1)) {
+        pending.push(chunk.to_vec());
+    }
+
+    let mut remaining = Vec::new();
+    for chunk in pending {
+        match client
+            .post(&consumption.endpoint)
+            .json(&serde_json::json!({ "events": chunk }))
+            .send()
+            .await
+            .and_then(|resp| resp.error_for_status())
+        {
+            Ok(_) => debug!("Uploaded consumption-metrics chunk of {} event(s)", chunk.len()),
+            Err(e) => {
+                warn!("Consumption-metrics chunk upload failed, will retry next interval: {}", e);
+                remaining.push(chunk);
+            }
+        }
+    }
+
+    cache.baseline = new_baseline;
+    cache.last_window_end = now;
+    cache.pending_chunks = remaining;
+    save_consumption_cache(&consumption.cache_path, &cache)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_percentile_matches_known_distribution() {
+        let metrics = ResponseTimeMetrics::new();
+        for value in 1..=100u64 {
+            metrics.histogram[histogram_bucket_index(value)].fetch_add(1, Ordering::Relaxed);
+        }
+
+        let buckets = metrics.histogram_snapshot();
+        let p50 = histogram_percentile(&buckets, 0.50);
+        let p99 = histogram_percentile(&buckets, 0.99);
+
+        // Log-linear bucketing trades exactness for O(1) recording, so allow
+        // the ~12.5% relative error the precision setting implies.
+        assert!((40..=60).contains(&p50), "p50 {} out of expected range", p50);
+        assert!((90..=110).contains(&p99), "p99 {} out of expected range", p99);
+    }
+
+    #[test]
+    fn test_histogram_bucket_index_is_monotonic() {
+        let mut last_index = 0;
+        for value in [0u64, 1, 7, 8, 15, 16, 1000, 1_000_000] {
+            let index = histogram_bucket_index(value);
+            assert!(index >= last_index, "bucket index should not decrease as value grows");
+            last_index = index;
+        }
+    }
+
+    #[test]
+    fn test_consumption_idempotency_key_is_deterministic_and_window_specific() {
+        let a = consumption_idempotency_key("tools/call", 1000, "node-a");
+        let b = consumption_idempotency_key("tools/call", 1000, "node-a");
+        let c = consumption_idempotency_key("tools/call", 2000, "node-a");
+        let d = consumption_idempotency_key("tools/call", 1000, "node-b");
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_ne!(a, d);
+    }
+
+    #[test]
+    fn test_consumption_cache_round_trips_and_preserves_pending_chunks() {
+        let mut cache = ConsumptionUploadCache::default();
+        cache.baseline.insert("tools/call".to_string(), (10, 2));
+        cache.last_window_end = 1234;
+        cache.pending_chunks.push(vec![ConsumptionEvent {
+            endpoint: "tools/call".to_string(),
+            request_count: 5,
+            error_count: 1,
+            window_start: 1000,
+            window_end: 1234,
+            idempotency_key: consumption_idempotency_key("tools/call", 1000, "node-a"),
+        }]);
+
+        let path = std::env::temp_dir().join(format!(
+            "deepseek-mcp-bridge-consumption-cache-test-{}-{}.json",
+            std::process::id(),
+            "round-trip"
+        ));
+
+        save_consumption_cache(&path, &cache).unwrap();
+        let loaded = load_consumption_cache(&path);
+
+        assert_eq!(loaded.baseline.get("tools/call"), Some(&(10, 2)));
+        assert_eq!(loaded.last_window_end, 1234);
+        assert_eq!(loaded.pending_chunks.len(), 1);
+        assert_eq!(loaded.pending_chunks[0][0].request_count, 5);
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_rate_window_sums_only_recent_slots() {
+        let window = RateWindow::new();
+        for second in 1000..1010u64 {
+            window.record_request(second);
+        }
+        window.record_error(1005);
+
+        let (requests, errors) = window.totals(1009);
+        assert_eq!(requests, 10);
+        assert_eq!(errors, 1);
+    }
+
+    #[test]
+    fn test_metric_labels_key_identically_regardless_of_insertion_order() {
+        let a = MetricLabels::new("tools/call").with("status", "200").with("model", "deepseek-chat");
+        let b = MetricLabels::new("tools/call").with("model", "deepseek-chat").with("status", "200");
+
+        assert_eq!(a, b);
+
+        let mut map: std::collections::HashMap<MetricLabels, u32> = std::collections::HashMap::new();
+        map.insert(a, 1);
+        assert_eq!(map.get(&b), Some(&1));
+    }
+
+    #[test]
+    fn test_metric_labels_to_prometheus_escapes_special_characters() {
+        let labels = MetricLabels::new("tools/call").with("reason", "bad \"input\"\nline");
+        let rendered = labels.to_prometheus();
+
+        assert!(rendered.contains(r#"endpoint="tools/call""#));
+        assert!(rendered.contains(r#"reason="bad \"input\"\nline""#));
+    }
+
+    #[test]
+    fn test_rate_window_expires_slots_older_than_the_window() {
+        let window = RateWindow::new();
+        for second in 0..5u64 {
+            window.record_request(second);
+        }
+
+        // Jump forward well past the window length; every earlier slot should
+        // have been zeroed rather than still counted.
+        let (requests, _) = window.totals(1000);
+        assert_eq!(requests, 0);
+
+        window.record_request(1000);
+        let (requests, _) = window.totals(1000);
+        assert_eq!(requests, 1);
+    }
 }
\ No newline at end of file
@@ -6,9 +6,11 @@ use dashmap::DashMap;
 use governor::{Quota, RateLimiter, state::{InMemoryState, NotKeyed}};
 use leaky_bucket::RateLimiter as LeakyBucketLimiter;
 use serde::{Deserialize, Serialize};
-use std::net::IpAddr;
-use std::sync::Arc;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::sync::{Arc, OnceLock};
 use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::timeout;
 use tracing::{warn, info, debug, error};
 use uuid::Uuid;
 
@@ -22,6 +24,37 @@ pub struct RateLimitConfig {
     pub burst_allowance: u32,
     pub tool_specific_limits: ToolSpecificLimits,
     pub enabled: bool,
+    /// Bits of an IPv6 address kept when grouping clients into a rate-limit
+    /// bucket. Defaults to /64, the smallest allocation routed to a single site,
+    /// so rotating through addresses within one allocation doesn't evade limits.
+    pub ipv6_prefix_len: u8,
+    /// Coarser IPv6 grouping used only for suspicious-activity escalation, so a
+    /// hostile network spreading traffic across many /64s within one /48 still
+    /// accumulates a single risk score.
+    pub ipv6_coarse_prefix_len: u8,
+    /// Bits of an IPv4 address kept when grouping clients into a rate-limit
+    /// bucket. Defaults to /32 (exact address); set to /24 to group by subnet.
+    pub ipv4_prefix_len: u8,
+    /// Max requests a single client may have in flight at once, regardless of
+    /// tool. Bounds how many slow concurrent requests one client can hold open
+    /// within its rate budget.
+    pub max_concurrent_per_client: u32,
+    /// Per-minute request budget granted instead of `per_client_requests_per_minute`
+    /// to a client that presented a valid API key via `ApiKeyManager`, so trusted
+    /// integrations aren't throttled to the same budget as anonymous callers.
+    pub authenticated_per_client_requests_per_minute: u32,
+    /// Concurrency budget granted instead of `max_concurrent_per_client` to an
+    /// authenticated client.
+    pub authenticated_max_concurrent_per_client: u32,
+    /// Process-wide concurrency budget, independent of any single client's
+    /// budget. Kept in lockstep with `performance.connection_pool_size` so the
+    /// bridge never holds more concurrent DeepSeek calls open than it has
+    /// pooled connections for.
+    pub global_concurrency_limit: usize,
+    /// How long `acquire_concurrency_permit` waits for a slot before giving up
+    /// and returning `RateLimited`, so a short burst queues briefly instead of
+    /// failing instantly while still bounding worst-case latency.
+    pub concurrency_acquire_timeout_ms: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,6 +63,10 @@ pub struct ToolSpecificLimits {
     pub file_analysis_per_minute: u32,
     pub health_check_per_minute: u32,
     pub heavy_operations_per_hour: u32,
+    /// Max `deepseek_query` calls a single client may have in flight at once.
+    pub deepseek_query_max_concurrent: u32,
+    /// Max `file_analysis` calls a single client may have in flight at once.
+    pub file_analysis_max_concurrent: u32,
 }
 
 impl Default for RateLimitConfig {
@@ -45,8 +82,18 @@ impl Default for RateLimitConfig {
                 file_analysis_per_minute: 20,
                 health_check_per_minute: 60,
                 heavy_operations_per_hour: 100,
+                deepseek_query_max_concurrent: 3,
+                file_analysis_max_concurrent: 3,
             },
             enabled: true,
+            ipv6_prefix_len: 64,
+            ipv6_coarse_prefix_len: 48,
+            ipv4_prefix_len: 32,
+            max_concurrent_per_client: 5,
+            authenticated_per_client_requests_per_minute: 300,
+            authenticated_max_concurrent_per_client: 15,
+            global_concurrency_limit: 50,
+            concurrency_acquire_timeout_ms: 250,
         }
     }
 }
@@ -55,6 +102,10 @@ impl Default for RateLimitConfig {
 #[derive(Debug)]
 pub enum RateLimitDecision {
     Allowed,
+    /// Like `Allowed`, but also carries the concurrency slot(s) reserved for
+    /// this request. Hold the permit for the lifetime of the in-flight request;
+    /// dropping it (e.g. when the caller's scope ends) releases the slot.
+    AllowedWithPermit(ConcurrencyPermit),
     RateLimited {
         retry_after_seconds: u64,
         limit_type: String,
@@ -63,6 +114,16 @@ pub enum RateLimitDecision {
     },
 }
 
+/// RAII guard for the concurrency slot(s) acquired alongside a rate-limit
+/// decision. Dropping it releases the client (and, if applicable, tool-category)
+/// semaphore permits - there is nothing else to call.
+#[derive(Debug)]
+pub struct ConcurrencyPermit {
+    _global: OwnedSemaphorePermit,
+    _client: OwnedSemaphorePermit,
+    _tool: Option<OwnedSemaphorePermit>,
+}
+
 /// Client identifier for rate limiting
 #[derive(Debug, Clone, Hash, Eq, PartialEq)]
 pub struct ClientIdentifier {
@@ -95,11 +156,27 @@ impl ClientIdentifier {
         self
     }
     
-    /// Generate a unique key for rate limiting
+    /// Generate a unique key for rate limiting, grouping IPv6 clients by /64 and
+    /// IPv4 clients by exact address. Equivalent to
+    /// `key_with_prefix(64, 32)` - use `key_with_prefix` directly wherever the
+    /// configured `RateLimitConfig` prefix lengths are available.
     pub fn key(&self) -> String {
+        self.key_with_prefix(64, 32)
+    }
+
+    /// Generate a rate-limit key, masking an IPv6 address to its leading
+    /// `ipv6_prefix_len` bits and an IPv4 address to its leading
+    /// `ipv4_prefix_len` bits before building the key. This keeps an attacker
+    /// with a routed allocation from evading per-IP limits by rotating through
+    /// addresses within it.
+    pub fn key_with_prefix(&self, ipv6_prefix_len: u8, ipv4_prefix_len: u8) -> String {
         match (&self.ip, &self.client_id) {
-            (Some(ip), Some(client_id)) => format!("{}:{}", ip, client_id),
-            (Some(ip), None) => format!("ip:{}", ip),
+            (Some(ip), Some(client_id)) => format!(
+                "{}:{}",
+                mask_ip(ip, ipv6_prefix_len, ipv4_prefix_len),
+                client_id
+            ),
+            (Some(ip), None) => format!("ip:{}", mask_ip(ip, ipv6_prefix_len, ipv4_prefix_len)),
             (None, Some(client_id)) => format!("client:{}", client_id),
             (None, None) => {
                 // Fallback to user agent hash if available
@@ -117,6 +194,26 @@ impl ClientIdentifier {
     }
 }
 
+/// Mask `ip` to its leading `ipv6_prefix_len`/`ipv4_prefix_len` bits, returning
+/// a `masked-address/prefix-len` string so multiple distinct source addresses
+/// within the same allocation collapse onto one rate-limit bucket.
+fn mask_ip(ip: &IpAddr, ipv6_prefix_len: u8, ipv4_prefix_len: u8) -> String {
+    match ip {
+        IpAddr::V4(v4) => {
+            let prefix_len = ipv4_prefix_len.min(32);
+            let mask = if prefix_len == 0 { 0 } else { !0u32 << (32 - prefix_len) };
+            let masked = Ipv4Addr::from(u32::from(*v4) & mask);
+            format!("{}/{}", masked, prefix_len)
+        }
+        IpAddr::V6(v6) => {
+            let prefix_len = ipv6_prefix_len.min(128);
+            let mask = if prefix_len == 0 { 0 } else { !0u128 << (128 - prefix_len) };
+            let masked = Ipv6Addr::from(u128::from(*v6) & mask);
+            format!("{}/{}", masked, prefix_len)
+        }
+    }
+}
+
 /// Request context for rate limiting
 #[derive(Debug, Clone)]
 pub struct RequestContext {
@@ -125,53 +222,347 @@ pub struct RequestContext {
     pub tool_name: Option<String>,
     pub timestamp: Instant,
     pub request_size: usize,
+    /// Correlation id for this request, linking its debug log line, audit
+    /// record and client-visible error together. Generated once per request
+    /// in `DeepSeekMcpHandler::handle_stdio_request`, or taken from an inbound
+    /// `params._meta.requestId` when the client supplied one.
+    pub request_id: String,
+    /// Whether the caller presented a valid API key via `ApiKeyManager` for
+    /// this request. Grants the `authenticated_*` bonus limits in
+    /// `RateLimitConfig` instead of the anonymous defaults.
+    pub authenticated: bool,
+}
+
+/// `Instant` is process-relative and has no fixed epoch, so every entry needs a
+/// shared zero point to express "last checked" as a compact `u32` second offset
+/// instead of a full 16-byte `Instant`.
+fn process_start() -> Instant {
+    static PROCESS_START: OnceLock<Instant> = OnceLock::new();
+    *PROCESS_START.get_or_init(Instant::now)
+}
+
+fn now_secs() -> u32 {
+    process_start().elapsed().as_secs() as u32
 }
 
-/// Rate limiter tracking entry
+/// Allowance value an entry is constructed with, meaning "never checked yet" so
+/// the first `try_consume` initializes the bucket to full capacity rather than
+/// refilling from zero.
+const UNINITIALIZED_ALLOWANCE: f32 = -2.0;
+
+/// Per-client token bucket. Holds a compact `u32` second offset and an `f32`
+/// allowance - 8 bytes total - instead of an unbounded `Vec<Instant>`, so
+/// per-client memory is flat regardless of burst volume and `check` stays O(1).
 #[derive(Debug)]
 struct RateLimitEntry {
-    requests: Vec<Instant>,
-    last_request: Instant,
+    last_checked_secs: u32,
+    allowance: f32,
     total_requests: u64,
     blocked_requests: u64,
+    // Coarse per-second request counter, used only to flag bursts for the
+    // suspicious-activity heuristic; reset whenever the wall-clock second ticks over.
+    requests_this_second: u32,
 }
 
 impl RateLimitEntry {
     fn new() -> Self {
         Self {
-            requests: Vec::new(),
-            last_request: Instant::now(),
+            last_checked_secs: now_secs(),
+            allowance: UNINITIALIZED_ALLOWANCE,
             total_requests: 0,
             blocked_requests: 0,
+            requests_this_second: 0,
         }
     }
-    
-    /// Clean up old request timestamps
-    fn cleanup_old_requests(&mut self, window_duration: Duration) {
-        let cutoff = Instant::now() - window_duration;
-        self.requests.retain(|&timestamp| timestamp > cutoff);
+
+    /// Refill the bucket for elapsed time and attempt to consume one token.
+    /// Returns the number of seconds until a token is available if the bucket
+    /// is empty.
+    fn try_consume(&mut self, capacity: f32, refill_window_secs: f32) -> std::result::Result<(), u64> {
+        let now = now_secs();
+        let refill_rate = capacity / refill_window_secs;
+
+        if self.allowance <= UNINITIALIZED_ALLOWANCE {
+            self.allowance = capacity;
+        } else {
+            let elapsed = now.saturating_sub(self.last_checked_secs) as f32;
+            self.allowance = (self.allowance + elapsed * refill_rate).min(capacity);
+        }
+
+        if now != self.last_checked_secs {
+            self.requests_this_second = 0;
+        }
+        self.requests_this_second += 1;
+        self.last_checked_secs = now;
+
+        if self.allowance < 1.0 {
+            let seconds_needed = ((1.0 - self.allowance) / refill_rate).ceil().max(1.0) as u64;
+            Err(seconds_needed)
+        } else {
+            self.allowance -= 1.0;
+            Ok(())
+        }
     }
-    
-    /// Count requests in the given window
-    fn count_requests_in_window(&self, window_duration: Duration) -> usize {
-        let cutoff = Instant::now() - window_duration;
-        self.requests.iter().filter(|&&timestamp| timestamp > cutoff).count()
+
+    /// Whether this bucket has refilled back to full capacity, making it safe
+    /// for `cleanup_old_entries` to drop without losing any pending state.
+    fn is_fully_refilled(&self, capacity: f32) -> bool {
+        self.allowance >= capacity
     }
-    
-    /// Add a new request
-    fn add_request(&mut self) {
-        let now = Instant::now();
-        self.requests.push(now);
-        self.last_request = now;
+
+    fn record_request(&mut self) {
         self.total_requests += 1;
     }
-    
+
     /// Record a blocked request
     fn record_blocked(&mut self) {
         self.blocked_requests += 1;
     }
 }
 
+/// Outcome of a per-client rate-limit store check.
+#[derive(Debug, Clone, Copy)]
+pub struct StoreDecision {
+    pub allowed: bool,
+    pub current_count: u32,
+}
+
+/// Pluggable backend for the per-client counter behind `check_per_client_limit`.
+/// `InMemoryRateLimitStore` is per-process, which multiplies every configured
+/// limit once the bridge runs as several replicas; `RedisRateLimitStore` keeps
+/// the authoritative count in Redis so the limit holds across the cluster.
+#[async_trait::async_trait]
+pub trait RateLimitStore: Send + Sync {
+    async fn check_and_increment(&self, key: &str, capacity: u32, window_secs: u64) -> Result<StoreDecision>;
+}
+
+/// Default, single-process store backed by the same token-bucket `RateLimitEntry`
+/// used for client bookkeeping elsewhere in `SecurityRateLimiter`.
+pub struct InMemoryRateLimitStore {
+    entries: Arc<DashMap<String, RateLimitEntry>>,
+}
+
+impl InMemoryRateLimitStore {
+    fn new(entries: Arc<DashMap<String, RateLimitEntry>>) -> Self {
+        Self { entries }
+    }
+}
+
+#[async_trait::async_trait]
+impl RateLimitStore for InMemoryRateLimitStore {
+    async fn check_and_increment(&self, key: &str, capacity: u32, window_secs: u64) -> Result<StoreDecision> {
+        let mut entry = self.entries.entry(key.to_string()).or_insert_with(RateLimitEntry::new);
+        let capacity_f = capacity as f32;
+        match entry.try_consume(capacity_f, window_secs as f32) {
+            Ok(()) => Ok(StoreDecision { allowed: true, current_count: capacity }),
+            Err(_) => {
+                entry.record_blocked();
+                Ok(StoreDecision { allowed: false, current_count: capacity + 1 })
+            }
+        }
+    }
+}
+
+/// A client's last known remaining allowance, refreshed periodically from Redis
+/// rather than on every request.
+struct CachedAllowance {
+    remaining: u32,
+    refreshed_at: Instant,
+}
+
+/// Redis-backed store for multi-replica deployments. A short-TTL local cache
+/// sits in front of Redis: while a client has comfortable headroom, requests are
+/// decided locally against the cached allowance; once the cache is stale or
+/// nearly exhausted, the authoritative `INCR`/`EXPIRE` round trip runs instead.
+pub struct RedisRateLimitStore {
+    client: redis::Client,
+    local_cache: Arc<DashMap<String, CachedAllowance>>,
+    cache_ttl: Duration,
+}
+
+impl RedisRateLimitStore {
+    pub fn new(redis_url: &str, cache_ttl: Duration) -> Result<Self> {
+        Ok(Self {
+            client: redis::Client::open(redis_url)?,
+            local_cache: Arc::new(DashMap::new()),
+            cache_ttl,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl RateLimitStore for RedisRateLimitStore {
+    async fn check_and_increment(&self, key: &str, capacity: u32, window_secs: u64) -> Result<StoreDecision> {
+        let has_local_headroom = self
+            .local_cache
+            .get(key)
+            .map(|cached| cached.refreshed_at.elapsed() < self.cache_ttl && cached.remaining > 1)
+            .unwrap_or(false);
+
+        if has_local_headroom {
+            let mut cached = self.local_cache.get_mut(key).unwrap();
+            cached.remaining -= 1;
+            return Ok(StoreDecision {
+                allowed: true,
+                current_count: capacity.saturating_sub(cached.remaining),
+            });
+        }
+
+        let mut conn = self.client.get_multiplexed_async_connection().await?;
+        // `EXPIRE ... NX` only sets the TTL if the key doesn't already carry one,
+        // so a window's expiry is fixed at the first request that creates the key
+        // rather than being pushed back by every subsequent increment - otherwise
+        // a client that keeps retrying inside the window never ages out of it.
+        let count: u32 = redis::pipe()
+            .atomic()
+            .incr(key, 1)
+            .cmd("EXPIRE")
+            .arg(key)
+            .arg(window_secs as i64)
+            .arg("NX")
+            .ignore()
+            .query_async(&mut conn)
+            .await?;
+
+        self.local_cache.insert(
+            key.to_string(),
+            CachedAllowance {
+                remaining: capacity.saturating_sub(count),
+                refreshed_at: Instant::now(),
+            },
+        );
+
+        Ok(StoreDecision {
+            allowed: count <= capacity,
+            current_count: count,
+        })
+    }
+}
+
+/// Registers in the `blocked_client_sketch` HyperLogLog, 2^12 = 4096. Bounds
+/// its memory at a few KB regardless of how many distinct clients are ever
+/// seen, at the cost of a few percent of estimation error.
+const HLL_REGISTER_BITS: u32 = 12;
+const HLL_REGISTER_COUNT: usize = 1 << HLL_REGISTER_BITS;
+
+/// Fixed-size HyperLogLog sketch estimating the number of distinct client keys
+/// that have ever been rate-limit-blocked. Unlike `suspicious_clients.len()`,
+/// this survives `cleanup_old_entries` dropping per-client state, so operators
+/// can see unique-blocked-client cardinality over an arbitrarily long window
+/// without unbounded memory.
+struct HyperLogLog {
+    registers: std::sync::Mutex<[u8; HLL_REGISTER_COUNT]>,
+}
+
+impl HyperLogLog {
+    fn new() -> Self {
+        Self {
+            registers: std::sync::Mutex::new([0u8; HLL_REGISTER_COUNT]),
+        }
+    }
+
+    /// Hash `key`, use its top `HLL_REGISTER_BITS` bits to pick a register and
+    /// the leading-zero-count of the remaining bits to update that register's
+    /// observed max run length.
+    fn insert(&self, key: &str) {
+        use sha2::{Digest, Sha256};
+        let mut hasher = Sha256::new();
+        hasher.update(key.as_bytes());
+        let hash = hasher.finalize();
+        let value = u64::from_be_bytes(hash[0..8].try_into().unwrap());
+
+        let index = (value >> (64 - HLL_REGISTER_BITS)) as usize;
+        let remainder = value << HLL_REGISTER_BITS;
+        let rank = (remainder.leading_zeros() + 1) as u8;
+
+        let mut registers = self.registers.lock().unwrap();
+        if rank > registers[index] {
+            registers[index] = rank;
+        }
+    }
+
+    /// Standard HyperLogLog harmonic-mean estimator, with the small-range
+    /// (linear counting) and large-range corrections from the original paper.
+    fn estimate(&self) -> u64 {
+        let registers = self.registers.lock().unwrap();
+        let m = HLL_REGISTER_COUNT as f64;
+        let alpha_m = 0.7213 / (1.0 + 1.079 / m);
+
+        let sum: f64 = registers.iter().map(|&r| 2f64.powi(-(r as i32))).sum();
+        let raw_estimate = alpha_m * m * m / sum;
+
+        let zero_registers = registers.iter().filter(|&&r| r == 0).count();
+
+        let two_pow_32 = (1u64 << 32) as f64;
+        let estimate = if raw_estimate <= 2.5 * m && zero_registers > 0 {
+            m * (m / zero_registers as f64).ln()
+        } else if raw_estimate <= two_pow_32 / 30.0 {
+            raw_estimate
+        } else {
+            -two_pow_32 * (1.0 - raw_estimate / two_pow_32).ln()
+        };
+
+        estimate.round().max(0.0) as u64
+    }
+}
+
+/// A single configured rate-limit bucket: its capacity, refill window, and the
+/// tool names that route to it. Building this table from `RateLimitConfig`
+/// (rather than matching tool names inline in `check_tool_specific_limit`)
+/// means a new limit class - or a new tool joining an existing one - is a
+/// config and registry change, not a new match arm.
+#[derive(Debug, Clone)]
+struct BucketConfig {
+    name: String,
+    capacity: u32,
+    refill_window_secs: u64,
+    tool_names: Vec<&'static str>,
+}
+
+/// Data-driven registry mapping tool names to the `BucketConfig` that governs
+/// them, built once from `RateLimitConfig` at construction.
+struct RateLimitRegistry {
+    buckets: Vec<BucketConfig>,
+}
+
+impl RateLimitRegistry {
+    fn from_config(limits: &ToolSpecificLimits) -> Self {
+        Self {
+            buckets: vec![
+                BucketConfig {
+                    name: "deepseek_query".to_string(),
+                    capacity: limits.deepseek_query_per_minute,
+                    refill_window_secs: 60,
+                    tool_names: vec!["enhanced_query_deepseek", "query_deepseek"],
+                },
+                BucketConfig {
+                    name: "file_analysis".to_string(),
+                    capacity: limits.file_analysis_per_minute,
+                    refill_window_secs: 60,
+                    tool_names: vec!["analyze_files", "youtu_agent_analyze_files"],
+                },
+                BucketConfig {
+                    name: "health_check".to_string(),
+                    capacity: limits.health_check_per_minute,
+                    refill_window_secs: 60,
+                    tool_names: vec!["check_deepseek_status", "health"],
+                },
+                BucketConfig {
+                    name: "heavy_operations".to_string(),
+                    capacity: limits.heavy_operations_per_hour,
+                    refill_window_secs: 3600,
+                    tool_names: vec!["heavy_operation"],
+                },
+            ],
+        }
+    }
+
+    /// The bucket configured for `tool_name`, if any tool names it.
+    fn bucket_for(&self, tool_name: &str) -> Option<&BucketConfig> {
+        self.buckets.iter().find(|b| b.tool_names.contains(&tool_name))
+    }
+}
+
 /// Multi-layer rate limiter with different strategies
 pub struct SecurityRateLimiter {
     config: RateLimitConfig,
@@ -179,10 +570,25 @@ pub struct SecurityRateLimiter {
     global_limiter: Arc<RateLimiter<NotKeyed, InMemoryState, governor::clock::DefaultClock>>,
     // Per-client tracking
     client_limits: Arc<DashMap<String, RateLimitEntry>>,
-    // Tool-specific limiters
+    // Per-client rate-limit decision backend: in-memory by default, or Redis
+    // (via `with_store`) when limits must hold across multiple replicas.
+    store: Arc<dyn RateLimitStore>,
+    // Data-driven tool-name -> bucket mapping
+    registry: RateLimitRegistry,
+    // Tool-specific limiters, keyed by `BucketConfig::name`
     tool_limiters: Arc<DashMap<String, Arc<LeakyBucketLimiter>>>,
     // Suspicious activity tracking
     suspicious_clients: Arc<DashMap<String, SuspiciousActivityTracker>>,
+    // Per-client concurrency limiting, independent of request rate
+    client_semaphores: Arc<DashMap<String, Arc<Semaphore>>>,
+    // Per-tool-category concurrency limiting (keyed like `tool_limiters`)
+    tool_semaphores: Arc<DashMap<String, Arc<Semaphore>>>,
+    // Process-wide concurrency budget, acquired before any per-client or
+    // per-tool-category slot so a burst spread across many clients still
+    // can't exceed `config.global_concurrency_limit` in-flight requests.
+    global_semaphore: Arc<Semaphore>,
+    // Bounded-memory estimate of distinct blocked clients, surviving cleanup
+    blocked_client_sketch: HyperLogLog,
 }
 
 #[derive(Debug)]
@@ -229,25 +635,30 @@ impl SecurityRateLimiter {
         let global_quota = Quota::per_second(std::num::NonZeroU32::new(config.global_requests_per_second).unwrap());
         let global_limiter = Arc::new(RateLimiter::direct(global_quota));
         
-        // Initialize tool-specific limiters
+        // Build the tool-name -> bucket registry, then a leaky-bucket limiter
+        // for every configured bucket (including the previously-orphaned
+        // health_check and heavy_operations buckets).
+        let registry = RateLimitRegistry::from_config(&config.tool_specific_limits);
         let tool_limiters = Arc::new(DashMap::new());
-        
-        // DeepSeek query limiter
-        let deepseek_limiter = LeakyBucketLimiter::builder()
-            .max(config.tool_specific_limits.deepseek_query_per_minute as usize)
-            .refill(1)
-            .interval(Duration::from_secs(60))
-            .build();
-        tool_limiters.insert("deepseek_query".to_string(), Arc::new(deepseek_limiter));
-        
-        // File analysis limiter
-        let file_limiter = LeakyBucketLimiter::builder()
-            .max(config.tool_specific_limits.file_analysis_per_minute as usize)
-            .refill(1)
-            .interval(Duration::from_secs(60))
-            .build();
-        tool_limiters.insert("file_analysis".to_string(), Arc::new(file_limiter));
-        
+        for bucket in &registry.buckets {
+            let limiter = LeakyBucketLimiter::builder()
+                .max(bucket.capacity as usize)
+                .refill(1)
+                .interval(Duration::from_secs(bucket.refill_window_secs))
+                .build();
+            tool_limiters.insert(bucket.name.clone(), Arc::new(limiter));
+        }
+
+        let tool_semaphores = Arc::new(DashMap::new());
+        tool_semaphores.insert(
+            "deepseek_query".to_string(),
+            Arc::new(Semaphore::new(config.tool_specific_limits.deepseek_query_max_concurrent as usize)),
+        );
+        tool_semaphores.insert(
+            "file_analysis".to_string(),
+            Arc::new(Semaphore::new(config.tool_specific_limits.file_analysis_max_concurrent as usize)),
+        );
+
         info!(
             "Rate limiter initialized: {}rps global, {}rpm per-IP, {}rpm per-client",
             config.global_requests_per_second,
@@ -255,26 +666,49 @@ impl SecurityRateLimiter {
             config.per_client_requests_per_minute
         );
         
+        let client_limits = Arc::new(DashMap::new());
+        let store = Arc::new(InMemoryRateLimitStore::new(client_limits.clone()));
+        let global_semaphore = Arc::new(Semaphore::new(config.global_concurrency_limit));
+
         Ok(Self {
             config,
             global_limiter,
-            client_limits: Arc::new(DashMap::new()),
+            client_limits,
+            store,
+            registry,
             tool_limiters,
             suspicious_clients: Arc::new(DashMap::new()),
+            client_semaphores: Arc::new(DashMap::new()),
+            tool_semaphores,
+            global_semaphore,
+            blocked_client_sketch: HyperLogLog::new(),
         })
     }
-    
+
+    /// Like [`SecurityRateLimiter::new`], but enforces the per-client limit
+    /// against `store` (e.g. a [`RedisRateLimitStore`]) instead of a
+    /// per-process map, so the limit holds across multiple bridge replicas.
+    /// Suspicious-activity tracking and statistics remain process-local.
+    pub fn with_store(config: RateLimitConfig, store: Arc<dyn RateLimitStore>) -> Result<Self> {
+        let mut limiter = Self::new(config)?;
+        limiter.store = store;
+        Ok(limiter)
+    }
+
     /// Check if request is allowed under all rate limiting rules
     pub async fn check_rate_limit(&self, request: &RequestContext) -> RateLimitDecision {
         if !self.config.enabled {
             return RateLimitDecision::Allowed;
         }
         
-        let client_key = request.client.key();
+        let client_key = request
+            .client
+            .key_with_prefix(self.config.ipv6_prefix_len, self.config.ipv4_prefix_len);
         
         // 1. Global rate limit check
         if let Err(_) = self.global_limiter.check() {
             warn!("Global rate limit exceeded for client: {}", client_key);
+            self.blocked_client_sketch.insert(&client_key);
             return RateLimitDecision::RateLimited {
                 retry_after_seconds: 1,
                 limit_type: "global".to_string(),
@@ -282,119 +716,235 @@ impl SecurityRateLimiter {
                 limit: self.config.global_requests_per_second,
             };
         }
-        
+
         // 2. Per-client rate limit check
-        let per_client_decision = self.check_per_client_limit(&client_key).await;
+        let per_client_decision = self.check_per_client_limit(&client_key, request.authenticated).await;
         if let RateLimitDecision::RateLimited { .. } = per_client_decision {
+            self.blocked_client_sketch.insert(&client_key);
             return per_client_decision;
         }
-        
+
         // 3. Tool-specific rate limit check
         if let Some(tool_name) = &request.tool_name {
             let tool_decision = self.check_tool_specific_limit(tool_name, &client_key).await;
             if let RateLimitDecision::RateLimited { .. } = tool_decision {
+                self.blocked_client_sketch.insert(&client_key);
                 return tool_decision;
             }
         }
-        
+
         // 4. Suspicious activity check
         let suspicious_decision = self.check_suspicious_activity(&request, &client_key).await;
         if let RateLimitDecision::RateLimited { .. } = suspicious_decision {
+            self.blocked_client_sketch.insert(&client_key);
             return suspicious_decision;
         }
-        
+
+        // 5. Concurrency limit check - reserve a slot for the lifetime of this
+        // request; the caller holds the returned permit until the request completes.
+        // Acquired only now, after every throttling decision above has passed,
+        // so a request that gets rate-limited never consumes a concurrency slot.
+        let permit = match self
+            .acquire_concurrency_permit(&client_key, request.tool_name.as_deref(), request.authenticated)
+            .await
+        {
+            Ok(permit) => permit,
+            Err(decision) => {
+                self.blocked_client_sketch.insert(&client_key);
+                return decision;
+            }
+        };
+
         // Record successful request
         self.record_request(&client_key, &request).await;
-        
+
         debug!("Rate limit check passed for client: {}", client_key);
-        RateLimitDecision::Allowed
+        RateLimitDecision::AllowedWithPermit(permit)
     }
-    
-    async fn check_per_client_limit(&self, client_key: &str) -> RateLimitDecision {
-        let mut entry = self.client_limits
+
+    /// Acquire the global, per-client (and, if `tool_name` maps to a known
+    /// category, per-tool-category) concurrency slot, in that order - the
+    /// global slot bounds the bridge's total in-flight DeepSeek calls to
+    /// `config.global_concurrency_limit` (mirroring `connection_pool_size`)
+    /// regardless of how many distinct clients are involved. Each acquire
+    /// waits up to `config.concurrency_acquire_timeout_ms` rather than
+    /// failing instantly, so a short burst queues briefly instead of being
+    /// rejected outright; a wait that times out still reports `RateLimited`.
+    /// `authenticated` grants the per-client slot the
+    /// `authenticated_max_concurrent_per_client` budget instead of
+    /// `max_concurrent_per_client` - note that budget is fixed the first time
+    /// a given `client_key` acquires a slot, since the semaphore is created
+    /// once and cached.
+    async fn acquire_concurrency_permit(
+        &self,
+        client_key: &str,
+        tool_name: Option<&str>,
+        authenticated: bool,
+    ) -> std::result::Result<ConcurrencyPermit, RateLimitDecision> {
+        let acquire_timeout = Duration::from_millis(self.config.concurrency_acquire_timeout_ms);
+
+        let global_permit = timeout(acquire_timeout, self.global_semaphore.clone().acquire_owned())
+            .await
+            .ok()
+            .and_then(|result| result.ok())
+            .ok_or_else(|| {
+                warn!("Global concurrency limit exceeded (budget: {})", self.config.global_concurrency_limit);
+                RateLimitDecision::RateLimited {
+                    retry_after_seconds: 1,
+                    limit_type: "concurrency_global".to_string(),
+                    current_count: self.config.global_concurrency_limit as u32 + 1,
+                    limit: self.config.global_concurrency_limit as u32,
+                }
+            })?;
+
+        let client_limit = if authenticated {
+            self.config.authenticated_max_concurrent_per_client
+        } else {
+            self.config.max_concurrent_per_client
+        };
+        let client_semaphore = self
+            .client_semaphores
             .entry(client_key.to_string())
-            .or_insert_with(RateLimitEntry::new);
-        
-        // Clean up old requests
-        entry.cleanup_old_requests(Duration::from_secs(60));
-        
-        let current_count = entry.count_requests_in_window(Duration::from_secs(60));
-        
-        if current_count >= self.config.per_client_requests_per_minute as usize {
-            entry.record_blocked();
+            .or_insert_with(|| Arc::new(Semaphore::new(client_limit as usize)))
+            .clone();
+
+        let client_permit = timeout(acquire_timeout, client_semaphore.acquire_owned())
+            .await
+            .ok()
+            .and_then(|result| result.ok())
+            .ok_or_else(|| {
+                warn!("Concurrency limit exceeded for client: {}", client_key);
+                RateLimitDecision::RateLimited {
+                    retry_after_seconds: 1,
+                    limit_type: "concurrency".to_string(),
+                    current_count: client_limit + 1,
+                    limit: client_limit,
+                }
+            })?;
+
+        let tool_category = tool_name.and_then(|name| self.registry.bucket_for(name)).map(|b| b.name.as_str());
+
+        let tool_permit = match tool_category {
+            Some(category) => match self.tool_semaphores.get(category) {
+                Some(semaphore) => {
+                    let limit = match category {
+                        "deepseek_query" => self.config.tool_specific_limits.deepseek_query_max_concurrent,
+                        "file_analysis" => self.config.tool_specific_limits.file_analysis_max_concurrent,
+                        _ => 0,
+                    };
+                    let permit = timeout(acquire_timeout, semaphore.clone().acquire_owned())
+                        .await
+                        .ok()
+                        .and_then(|result| result.ok())
+                        .ok_or_else(|| {
+                            warn!(
+                                "Tool-category concurrency limit exceeded for {}: {}",
+                                category, client_key
+                            );
+                            RateLimitDecision::RateLimited {
+                                retry_after_seconds: 1,
+                                limit_type: format!("concurrency_{}", category),
+                                current_count: limit + 1,
+                                limit,
+                            }
+                        })?;
+                    Some(permit)
+                }
+                None => None,
+            },
+            None => None,
+        };
+
+        Ok(ConcurrencyPermit {
+            _global: global_permit,
+            _client: client_permit,
+            _tool: tool_permit,
+        })
+    }
+
+    async fn check_per_client_limit(&self, client_key: &str, authenticated: bool) -> RateLimitDecision {
+        let capacity = if authenticated {
+            self.config.authenticated_per_client_requests_per_minute
+        } else {
+            self.config.per_client_requests_per_minute
+        };
+
+        let decision = match self.store.check_and_increment(client_key, capacity, 60).await {
+            Ok(decision) => decision,
+            Err(e) => {
+                error!("Rate limit store error for {}: {} - failing open", client_key, e);
+                return RateLimitDecision::Allowed;
+            }
+        };
+
+        if decision.allowed {
+            RateLimitDecision::Allowed
+        } else {
+            if let Some(mut entry) = self.client_limits.get_mut(client_key) {
+                entry.record_blocked();
+            }
             warn!(
                 "Per-client rate limit exceeded: {} (limit: {})",
-                current_count, self.config.per_client_requests_per_minute
+                decision.current_count, capacity
             );
-            
+
             RateLimitDecision::RateLimited {
                 retry_after_seconds: 60,
                 limit_type: "per_client".to_string(),
-                current_count: current_count as u32,
-                limit: self.config.per_client_requests_per_minute,
+                current_count: decision.current_count,
+                limit: capacity,
             }
-        } else {
-            RateLimitDecision::Allowed
         }
     }
     
+    /// Look up `tool_name`'s bucket in `self.registry` and enforce it. Tools
+    /// with no configured bucket (i.e. not listed in any `BucketConfig::tool_names`)
+    /// fall through unthrottled, same as the old `_ => "general"` arm's
+    /// no-op limiter lookup.
     async fn check_tool_specific_limit(&self, tool_name: &str, client_key: &str) -> RateLimitDecision {
-        let tool_category = match tool_name {
-            "enhanced_query_deepseek" | "query_deepseek" => "deepseek_query",
-            "analyze_files" | "youtu_agent_analyze_files" => "file_analysis",
-            "check_deepseek_status" | "health" => return RateLimitDecision::Allowed, // Health checks less restricted
-            _ => "general",
+        let bucket = match self.registry.bucket_for(tool_name) {
+            Some(bucket) => bucket,
+            None => return RateLimitDecision::Allowed,
         };
-        
-        if let Some(limiter) = self.tool_limiters.get(tool_category) {
+
+        if let Some(limiter) = self.tool_limiters.get(&bucket.name) {
             if !limiter.try_acquire(1) {
-                warn!("Tool-specific rate limit exceeded for {}: {}", tool_category, client_key);
-                
-                let limit = match tool_category {
-                    "deepseek_query" => self.config.tool_specific_limits.deepseek_query_per_minute,
-                    "file_analysis" => self.config.tool_specific_limits.file_analysis_per_minute,
-                    _ => 30,
-                };
-                
+                warn!("Tool-specific rate limit exceeded for {}: {}", bucket.name, client_key);
+
                 return RateLimitDecision::RateLimited {
-                    retry_after_seconds: 60,
-                    limit_type: format!("tool_{}", tool_category),
-                    current_count: limit + 1,
-                    limit,
+                    retry_after_seconds: bucket.refill_window_secs,
+                    limit_type: format!("tool_{}", bucket.name),
+                    current_count: bucket.capacity + 1,
+                    limit: bucket.capacity,
                 };
             }
         }
-        
+
         RateLimitDecision::Allowed
     }
     
     async fn check_suspicious_activity(&self, request: &RequestContext, client_key: &str) -> RateLimitDecision {
-        let mut tracker = self.suspicious_clients
-            .entry(client_key.to_string())
-            .or_insert_with(SuspiciousActivityTracker::new);
-        
-        // Detect rapid requests (more than 5 requests per second)
-        if let Some(entry) = self.client_limits.get(client_key) {
-            let recent_requests = entry.count_requests_in_window(Duration::from_secs(1));
-            if recent_requests > 5 {
-                tracker.rapid_requests_count += 1;
-            }
-        }
-        
-        // Detect large requests
-        if request.request_size > 100_000 {
-            tracker.large_requests_count += 1;
-        }
-        
-        let risk_score = tracker.calculate_risk_score();
-        
+        let fine_score = self.bump_suspicious_tracker(client_key, request);
+
+        // Also escalate at a coarser IPv6 grouping so a hostile network spreading
+        // traffic across many /64s within one /48 still accumulates a single
+        // risk score instead of looking clean in each narrow bucket.
+        let coarse_key = request
+            .client
+            .key_with_prefix(self.config.ipv6_coarse_prefix_len, self.config.ipv4_prefix_len);
+        let risk_score = if coarse_key == client_key {
+            fine_score
+        } else {
+            fine_score.max(self.bump_suspicious_tracker(&coarse_key, request))
+        };
+
         // Block high-risk clients
         if risk_score > 50 {
             error!(
                 "High-risk client blocked: {} (risk score: {})",
                 client_key, risk_score
             );
-            
+
             return RateLimitDecision::RateLimited {
                 retry_after_seconds: 300, // 5 minute cooldown
                 limit_type: "suspicious_activity".to_string(),
@@ -402,7 +952,7 @@ impl SecurityRateLimiter {
                 limit: 50,
             };
         }
-        
+
         // Warn about medium-risk clients
         if risk_score > 25 {
             warn!(
@@ -410,21 +960,43 @@ impl SecurityRateLimiter {
                 client_key, risk_score
             );
         }
-        
+
         RateLimitDecision::Allowed
     }
+
+    /// Update the suspicious-activity tracker for `key` from `request` and
+    /// return its freshly recomputed risk score.
+    fn bump_suspicious_tracker(&self, key: &str, request: &RequestContext) -> u32 {
+        let mut tracker = self.suspicious_clients
+            .entry(key.to_string())
+            .or_insert_with(SuspiciousActivityTracker::new);
+
+        // Detect rapid requests (more than 5 requests in the current second)
+        if let Some(entry) = self.client_limits.get(key) {
+            if entry.requests_this_second > 5 {
+                tracker.rapid_requests_count += 1;
+            }
+        }
+
+        // Detect large requests
+        if request.request_size > 100_000 {
+            tracker.large_requests_count += 1;
+        }
+
+        tracker.calculate_risk_score()
+    }
     
     async fn record_request(&self, client_key: &str, _request: &RequestContext) {
-        if let Some(mut entry) = self.client_limits.get_mut(client_key) {
-            entry.add_request();
-        } else {
-            let mut new_entry = RateLimitEntry::new();
-            new_entry.add_request();
-            self.client_limits.insert(client_key.to_string(), new_entry);
-        }
+        self.client_limits
+            .entry(client_key.to_string())
+            .or_insert_with(RateLimitEntry::new)
+            .record_request();
     }
     
-    /// Get rate limiting statistics
+    /// Get rate limiting statistics. Counters are process-local bookkeeping;
+    /// when running against a `RedisRateLimitStore`, the authoritative per-client
+    /// count lives in Redis and these totals only reflect requests seen by this
+    /// replica.
     pub fn get_statistics(&self) -> serde_json::Value {
         let total_clients = self.client_limits.len();
         let suspicious_clients = self.suspicious_clients.len();
@@ -436,7 +1008,47 @@ impl SecurityRateLimiter {
             total_requests += entry.total_requests;
             total_blocked += entry.blocked_requests;
         }
-        
+
+        // In-use concurrency permits, derived from each semaphore's configured
+        // capacity minus its currently available permits.
+        let client_concurrency_in_use: usize = self
+            .client_semaphores
+            .iter()
+            .map(|entry| (self.config.max_concurrent_per_client as usize).saturating_sub(entry.value().available_permits()))
+            .sum();
+
+        let mut tool_concurrency_in_use = serde_json::Map::new();
+        for entry in self.tool_semaphores.iter() {
+            let category = entry.key().as_str();
+            let capacity = match category {
+                "deepseek_query" => self.config.tool_specific_limits.deepseek_query_max_concurrent,
+                "file_analysis" => self.config.tool_specific_limits.file_analysis_max_concurrent,
+                _ => 0,
+            } as usize;
+            let in_use = capacity.saturating_sub(entry.value().available_permits());
+            tool_concurrency_in_use.insert(category.to_string(), serde_json::json!(in_use));
+        }
+
+        // Configured and remaining capacity for every registered bucket,
+        // including health_check and heavy_operations now that they're wired
+        // up through the registry instead of sitting unused in config.
+        let mut tool_buckets = serde_json::Map::new();
+        for bucket in &self.registry.buckets {
+            let remaining = self
+                .tool_limiters
+                .get(&bucket.name)
+                .map(|limiter| limiter.balance())
+                .unwrap_or(bucket.capacity as usize);
+            tool_buckets.insert(
+                bucket.name.clone(),
+                serde_json::json!({
+                    "capacity": bucket.capacity,
+                    "refill_window_secs": bucket.refill_window_secs,
+                    "remaining": remaining,
+                }),
+            );
+        }
+
         serde_json::json!({
             "total_clients": total_clients,
             "suspicious_clients": suspicious_clients,
@@ -447,6 +1059,10 @@ impl SecurityRateLimiter {
             } else {
                 0.0
             },
+            "client_concurrency_in_use": client_concurrency_in_use,
+            "tool_concurrency_in_use": tool_concurrency_in_use,
+            "estimated_unique_blocked_clients": self.blocked_client_sketch.estimate(),
+            "tool_buckets": tool_buckets,
             "config": {
                 "enabled": self.config.enabled,
                 "global_rps": self.config.global_requests_per_second,
@@ -460,12 +1076,14 @@ impl SecurityRateLimiter {
     /// Cleanup old entries to prevent memory leaks
     pub async fn cleanup_old_entries(&self) {
         let cutoff = Instant::now() - Duration::from_secs(3600); // 1 hour
-        
-        // Clean up client limits
+        let capacity = self.config.per_client_requests_per_minute as f32;
+
+        // A fully-refilled bucket has no pending state worth keeping - the next
+        // request for that client just starts a fresh one at full capacity.
         self.client_limits.retain(|_, entry| {
-            entry.last_request > cutoff
+            !entry.is_fully_refilled(capacity)
         });
-        
+
         // Clean up suspicious activity trackers
         self.suspicious_clients.retain(|_, tracker| {
             tracker.first_seen > cutoff
@@ -511,11 +1129,13 @@ mod tests {
                 tool_name: None,
                 timestamp: Instant::now(),
                 request_size: 1000,
+            request_id: "test-request".to_string(),
+            authenticated: false,
             };
             
             let decision = limiter.check_rate_limit(&request).await;
             match decision {
-                RateLimitDecision::Allowed => {},
+                RateLimitDecision::Allowed | RateLimitDecision::AllowedWithPermit(_) => {},
                 _ => panic!("Request {} should be allowed", i),
             }
         }
@@ -527,6 +1147,8 @@ mod tests {
             tool_name: None,
             timestamp: Instant::now(),
             request_size: 1000,
+        request_id: "test-request".to_string(),
+            authenticated: false,
         };
         
         let decision = limiter.check_rate_limit(&request).await;
@@ -550,6 +1172,8 @@ mod tests {
                 tool_name: None,
                 timestamp: Instant::now(),
                 request_size: 200_000, // Large request
+            request_id: "test-request".to_string(),
+            authenticated: false,
             };
             
             let _ = limiter.check_rate_limit(&request).await;
@@ -558,4 +1182,254 @@ mod tests {
         let stats = limiter.get_statistics();
         assert!(stats["suspicious_clients"].as_u64().unwrap() > 0);
     }
+
+    #[test]
+    fn test_rate_limit_entry_token_bucket_initializes_to_full_capacity() {
+        let mut entry = RateLimitEntry::new();
+
+        // First call initializes from UNINITIALIZED_ALLOWANCE to full capacity,
+        // so a brand-new client can burst up to its limit immediately.
+        for _ in 0..5 {
+            assert!(entry.try_consume(5.0, 60.0).is_ok());
+        }
+        assert!(entry.try_consume(5.0, 60.0).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_store_enforces_capacity() {
+        let store = InMemoryRateLimitStore::new(Arc::new(DashMap::new()));
+
+        for _ in 0..3 {
+            let decision = store.check_and_increment("client-a", 3, 60).await.unwrap();
+            assert!(decision.allowed);
+        }
+
+        let decision = store.check_and_increment("client-a", 3, 60).await.unwrap();
+        assert!(!decision.allowed);
+    }
+
+    #[test]
+    fn test_ipv6_clients_in_same_prefix_share_a_key() {
+        use std::net::Ipv6Addr;
+
+        let a = ClientIdentifier::new().with_ip(IpAddr::V6("2001:db8::1".parse::<Ipv6Addr>().unwrap()));
+        let b = ClientIdentifier::new().with_ip(IpAddr::V6("2001:db8::ffff".parse::<Ipv6Addr>().unwrap()));
+        let c = ClientIdentifier::new().with_ip(IpAddr::V6("2001:db8:1::1".parse::<Ipv6Addr>().unwrap()));
+
+        assert_eq!(a.key_with_prefix(64, 32), b.key_with_prefix(64, 32));
+        assert_ne!(a.key_with_prefix(64, 32), c.key_with_prefix(64, 32));
+    }
+
+    #[test]
+    fn test_ipv4_clients_grouped_by_subnet_when_configured() {
+        let a = ClientIdentifier::new().with_ip(IpAddr::V4(Ipv4Addr::new(203, 0, 113, 10)));
+        let b = ClientIdentifier::new().with_ip(IpAddr::V4(Ipv4Addr::new(203, 0, 113, 200)));
+
+        assert_ne!(a.key_with_prefix(64, 32), b.key_with_prefix(64, 32));
+        assert_eq!(a.key_with_prefix(64, 24), b.key_with_prefix(64, 24));
+    }
+
+    #[tokio::test]
+    async fn test_concurrency_limit_rejects_once_client_slots_are_exhausted() {
+        let config = RateLimitConfig {
+            max_concurrent_per_client: 2,
+            ..Default::default()
+        };
+        let limiter = SecurityRateLimiter::new(config).unwrap();
+        let client = ClientIdentifier::new().with_ip(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)));
+
+        let mut held_permits = Vec::new();
+        for _ in 0..2 {
+            let request = RequestContext {
+                client: client.clone(),
+                method: "test".to_string(),
+                tool_name: None,
+                timestamp: Instant::now(),
+                request_size: 1000,
+            request_id: "test-request".to_string(),
+            authenticated: false,
+            };
+            match limiter.check_rate_limit(&request).await {
+                RateLimitDecision::AllowedWithPermit(permit) => held_permits.push(permit),
+                other => panic!("expected a concurrency permit, got {:?}", other),
+            }
+        }
+
+        let request = RequestContext {
+            client: client.clone(),
+            method: "test".to_string(),
+            tool_name: None,
+            timestamp: Instant::now(),
+            request_size: 1000,
+        request_id: "test-request".to_string(),
+            authenticated: false,
+        };
+        match limiter.check_rate_limit(&request).await {
+            RateLimitDecision::RateLimited { limit_type, .. } => assert_eq!(limit_type, "concurrency"),
+            other => panic!("expected concurrency rejection, got {:?}", other),
+        }
+
+        // Releasing a permit frees the slot for the next request.
+        held_permits.pop();
+        let request = RequestContext {
+            client,
+            method: "test".to_string(),
+            tool_name: None,
+            timestamp: Instant::now(),
+            request_size: 1000,
+        request_id: "test-request".to_string(),
+            authenticated: false,
+        };
+        match limiter.check_rate_limit(&request).await {
+            RateLimitDecision::AllowedWithPermit(_) => {}
+            other => panic!("expected slot to be free again, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tool_category_concurrency_limit_is_independent_of_client_limit() {
+        let config = RateLimitConfig {
+            max_concurrent_per_client: 10,
+            tool_specific_limits: ToolSpecificLimits {
+                deepseek_query_max_concurrent: 1,
+                ..RateLimitConfig::default().tool_specific_limits
+            },
+            ..Default::default()
+        };
+        let limiter = SecurityRateLimiter::new(config).unwrap();
+        let client = ClientIdentifier::new().with_ip(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2)));
+
+        let request = RequestContext {
+            client: client.clone(),
+            method: "test".to_string(),
+            tool_name: Some("query_deepseek".to_string()),
+            timestamp: Instant::now(),
+            request_size: 1000,
+        request_id: "test-request".to_string(),
+            authenticated: false,
+        };
+        let _first = match limiter.check_rate_limit(&request).await {
+            RateLimitDecision::AllowedWithPermit(permit) => permit,
+            other => panic!("expected a concurrency permit, got {:?}", other),
+        };
+
+        let second_request = RequestContext {
+            client,
+            method: "test".to_string(),
+            tool_name: Some("query_deepseek".to_string()),
+            timestamp: Instant::now(),
+            request_size: 1000,
+        request_id: "test-request".to_string(),
+            authenticated: false,
+        };
+        match limiter.check_rate_limit(&second_request).await {
+            RateLimitDecision::RateLimited { limit_type, .. } => {
+                assert_eq!(limit_type, "concurrency_deepseek_query")
+            }
+            other => panic!("expected tool-category concurrency rejection, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_hyperloglog_estimates_distinct_keys_within_tolerance() {
+        let hll = HyperLogLog::new();
+        let distinct = 5_000;
+        for i in 0..distinct {
+            hll.insert(&format!("client-{}", i));
+        }
+
+        let estimate = hll.estimate();
+        let error = (estimate as f64 - distinct as f64).abs() / distinct as f64;
+        assert!(error < 0.1, "estimate {} too far from actual {}", estimate, distinct);
+    }
+
+    #[test]
+    fn test_hyperloglog_ignores_repeated_inserts() {
+        let hll = HyperLogLog::new();
+        for _ in 0..1000 {
+            hll.insert("same-client");
+        }
+
+        assert!(hll.estimate() <= 2, "repeated inserts of one key should not inflate the estimate");
+    }
+
+    #[tokio::test]
+    async fn test_blocked_clients_are_reflected_in_statistics() {
+        let config = RateLimitConfig {
+            per_client_requests_per_minute: 1,
+            ..Default::default()
+        };
+        let limiter = SecurityRateLimiter::new(config).unwrap();
+        let client = ClientIdentifier::new().with_ip(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 3)));
+
+        for _ in 0..2 {
+            let request = RequestContext {
+                client: client.clone(),
+                method: "test".to_string(),
+                tool_name: None,
+                timestamp: Instant::now(),
+                request_size: 1000,
+            request_id: "test-request".to_string(),
+            authenticated: false,
+            };
+            let _ = limiter.check_rate_limit(&request).await;
+        }
+
+        let stats = limiter.get_statistics();
+        assert!(stats["estimated_unique_blocked_clients"].as_u64().unwrap() >= 1);
+    }
+
+    #[tokio::test]
+    async fn test_previously_orphaned_buckets_are_now_enforced() {
+        let config = RateLimitConfig {
+            tool_specific_limits: ToolSpecificLimits {
+                health_check_per_minute: 2,
+                ..RateLimitConfig::default().tool_specific_limits
+            },
+            ..Default::default()
+        };
+        let limiter = SecurityRateLimiter::new(config).unwrap();
+        let client = ClientIdentifier::new().with_ip(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 4)));
+
+        for i in 0..2 {
+            let request = RequestContext {
+                client: client.clone(),
+                method: "test".to_string(),
+                tool_name: Some("health".to_string()),
+                timestamp: Instant::now(),
+                request_size: 100,
+            request_id: "test-request".to_string(),
+            authenticated: false,
+            };
+            match limiter.check_rate_limit(&request).await {
+                RateLimitDecision::AllowedWithPermit(_) => {}
+                other => panic!("health check {} should be allowed, got {:?}", i, other),
+            }
+        }
+
+        let request = RequestContext {
+            client,
+            method: "test".to_string(),
+            tool_name: Some("health".to_string()),
+            timestamp: Instant::now(),
+            request_size: 100,
+        request_id: "test-request".to_string(),
+            authenticated: false,
+        };
+        match limiter.check_rate_limit(&request).await {
+            RateLimitDecision::RateLimited { limit_type, .. } => assert_eq!(limit_type, "tool_health_check"),
+            other => panic!("health check should now be rate limited, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_registry_maps_tool_names_to_configured_buckets() {
+        let registry = RateLimitRegistry::from_config(&RateLimitConfig::default().tool_specific_limits);
+
+        assert_eq!(registry.bucket_for("query_deepseek").unwrap().name, "deepseek_query");
+        assert_eq!(registry.bucket_for("analyze_files").unwrap().name, "file_analysis");
+        assert_eq!(registry.bucket_for("health").unwrap().name, "health_check");
+        assert_eq!(registry.bucket_for("heavy_operation").unwrap().name, "heavy_operations");
+        assert!(registry.bucket_for("unlisted_tool").is_none());
+    }
 }
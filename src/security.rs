@@ -3,12 +3,21 @@
 
 use anyhow::Result;
 use base64::{Engine as _, engine::general_purpose};
+use hmac::{Hmac, Mac};
 use ring::digest::{Context, SHA256};
+use ring::signature::{self, UnparsedPublicKey, ED25519};
 use secrecy::{Secret, ExposeSecret};
 use serde::{Deserialize, Serialize};
+use sha1::{Digest as Sha1Digest, Sha1};
+use sha2::{Digest as Sha2Digest, Sha256 as Sha2Sha256};
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tracing::{error, warn, info, debug};
 use regex::Regex;
+use dashmap::DashMap;
+
+type HmacSha256 = Hmac<Sha2Sha256>;
 
 /// Secure configuration with secret protection
 #[derive(Clone)]
@@ -19,6 +28,10 @@ pub struct SecurityConfig {
     pub rate_limit_enabled: bool,
     pub audit_logging_enabled: bool,
     pub error_details_in_prod: bool,
+    pub credential_screening_enabled: bool,
+    pub default_bucket_capacity: u32,
+    pub default_refill_per_sec: u32,
+    pub path_bucket_overrides: HashMap<String, BucketOverride>,
 }
 
 impl SecurityConfig {
@@ -30,13 +43,60 @@ impl SecurityConfig {
             rate_limit_enabled: true,
             audit_logging_enabled: true,
             error_details_in_prod: false,
+            credential_screening_enabled: true,
+            default_bucket_capacity: 60,
+            default_refill_per_sec: 1,
+            path_bucket_overrides: HashMap::new(),
         }
     }
 }
 
+/// Lifecycle state of a managed symmetric key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyStatus {
+    /// Currently issued to clients and fully valid.
+    Active,
+    /// Superseded by a newer key but still accepted until `not_after`.
+    Deprecated,
+    /// Invalidated immediately; never accepted again.
+    Revoked,
+}
+
+/// A single symmetric credential tracked by `ApiKeyManager`.
+#[derive(Debug, Clone)]
+struct ManagedKey {
+    id: String,
+    hash: String,
+    not_after: Option<chrono::DateTime<chrono::Utc>>,
+    status: KeyStatus,
+}
+
+/// Authentication mode backing an `ApiKeyManager`.
+///
+/// `Symmetric` holds a set of active/deprecated/revoked keys so credentials can be
+/// rotated without locking out clients mid-flight; `Asymmetric` holds only a public
+/// key and verifies signed PASETO tokens, so the server never stores anything that
+/// lets it mint credentials itself.
+#[derive(Clone)]
+pub enum AuthMode {
+    Symmetric { keys: Vec<ManagedKey> },
+    Asymmetric { public_key: Vec<u8> },
+}
+
+/// Claims decoded from a verified `v4.public` PASETO token.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: String,
+    pub exp: chrono::DateTime<chrono::Utc>,
+    #[serde(default)]
+    pub nbf: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    pub scopes: Vec<String>,
+}
+
 /// API Key Manager - NEVER logs or exposes keys
 pub struct ApiKeyManager {
-    key_hash: String,
+    auth_mode: AuthMode,
     key_pattern: Regex,
 }
 
@@ -46,39 +106,649 @@ impl ApiKeyManager {
         let mut context = Context::new(&SHA256);
         context.update(api_key.expose_secret().as_bytes());
         let key_hash = general_purpose::STANDARD.encode(context.finish().as_ref());
-        
+
         // Validate API key format without logging
         let key_pattern = Regex::new(r"^[A-Za-z0-9_-]{32,128}$")?;
-        
+
         if !key_pattern.is_match(api_key.expose_secret()) {
             error!("API key format validation failed - check key structure");
             return Err(anyhow::anyhow!("Invalid API key format"));
         }
-        
+
         info!("API key securely configured with hash: {}...", &key_hash[..8]);
-        
+
         Ok(Self {
-            key_hash,
+            auth_mode: AuthMode::Symmetric {
+                keys: vec![ManagedKey {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    hash: key_hash,
+                    not_after: None,
+                    status: KeyStatus::Active,
+                }],
+            },
             key_pattern,
         })
     }
-    
-    /// Validate API key without logging the actual key
-    pub fn validate_key(&self, provided_key: &str) -> bool {
+
+    /// Admit `new_key` as the new active credential, keeping all previously active
+    /// keys valid (as `Deprecated`) until `grace_period` elapses from now. Returns
+    /// the new key's id so it can be attributed in audit logs.
+    pub fn rotate_in(&mut self, new_key: &Secret<String>, grace_period: chrono::Duration) -> Result<String> {
+        let keys = match &mut self.auth_mode {
+            AuthMode::Symmetric { keys } => keys,
+            AuthMode::Asymmetric { .. } => {
+                return Err(anyhow::anyhow!("Cannot rotate a symmetric key in asymmetric auth mode"));
+            }
+        };
+
+        if !self.key_pattern.is_match(new_key.expose_secret()) {
+            error!("API key rotation rejected - new key fails format validation");
+            return Err(anyhow::anyhow!("Invalid API key format"));
+        }
+
+        let grace_deadline = chrono::Utc::now() + grace_period;
+        for key in keys.iter_mut() {
+            if key.status == KeyStatus::Active {
+                key.status = KeyStatus::Deprecated;
+                key.not_after = Some(grace_deadline);
+            }
+        }
+
+        let mut context = Context::new(&SHA256);
+        context.update(new_key.expose_secret().as_bytes());
+        let hash = general_purpose::STANDARD.encode(context.finish().as_ref());
+
+        let new_id = uuid::Uuid::new_v4().to_string();
+        keys.push(ManagedKey {
+            id: new_id.clone(),
+            hash,
+            not_after: None,
+            status: KeyStatus::Active,
+        });
+
+        info!(
+            "API key rotated in (id: {}), previous key(s) valid until {}",
+            new_id, grace_deadline
+        );
+        Ok(new_id)
+    }
+
+    /// Immediately invalidate `key_id`, regardless of any remaining grace period.
+    pub fn revoke(&mut self, key_id: &str) -> Result<()> {
+        let keys = match &mut self.auth_mode {
+            AuthMode::Symmetric { keys } => keys,
+            AuthMode::Asymmetric { .. } => {
+                return Err(anyhow::anyhow!("Cannot revoke a symmetric key in asymmetric auth mode"));
+            }
+        };
+
+        match keys.iter_mut().find(|key| key.id == key_id) {
+            Some(key) => {
+                key.status = KeyStatus::Revoked;
+                key.not_after = None;
+                warn!("API key revoked (id: {})", key_id);
+                Ok(())
+            }
+            None => Err(anyhow::anyhow!("No such key id: {}", key_id)),
+        }
+    }
+
+    /// Like [`ApiKeyManager::new`], but first screens `api_key` against the
+    /// breach-corpus via [`CredentialScreener`] when
+    /// `security_config.credential_screening_enabled` is set, refusing to
+    /// configure a credential that's known to be leaked.
+    pub async fn new_with_screening(
+        api_key: &Secret<String>,
+        security_config: &SecurityConfig,
+    ) -> Result<Self> {
+        if security_config.credential_screening_enabled {
+            let screener = CredentialScreener::new();
+            if let Some(count) = screener.is_compromised(api_key).await? {
+                error!(
+                    "SECURITY: rejecting API key - found in breach corpus {} time(s)",
+                    count
+                );
+                return Err(anyhow::anyhow!(
+                    "API key rejected: found in known breach corpus"
+                ));
+            }
+        }
+
+        Self::new(api_key)
+    }
+
+    /// Construct a manager backed by an Ed25519 public key for PASETO `v4.public`
+    /// token verification. Token issuance (signing with the private key) is handled
+    /// by a separate offline helper - this manager never sees a private key.
+    pub fn new_asymmetric(public_key: &[u8]) -> Result<Self> {
+        if public_key.len() != 32 {
+            error!("Asymmetric auth configuration error - invalid public key length");
+            return Err(anyhow::anyhow!("Ed25519 public key must be 32 bytes"));
+        }
+
+        let key_pattern = Regex::new(r"^[A-Za-z0-9_-]{32,128}$")?;
+
+        info!("API key manager configured in asymmetric (PASETO v4.public) mode");
+
+        Ok(Self {
+            auth_mode: AuthMode::Asymmetric {
+                public_key: public_key.to_vec(),
+            },
+            key_pattern,
+        })
+    }
+
+    /// Validate an API key without logging the actual key, returning the id of the
+    /// matching credential (so audit logs can attribute which key was used) or
+    /// `None` if no active/in-grace key matches.
+    ///
+    /// Checks every managed key rather than returning as soon as one matches, so
+    /// the time this takes doesn't leak which key (if any) was the match.
+    pub fn validate_key(&self, provided_key: &str) -> Option<String> {
+        let keys = match &self.auth_mode {
+            AuthMode::Symmetric { keys } => keys,
+            AuthMode::Asymmetric { .. } => {
+                warn!("validate_key called while in asymmetric auth mode - use verify_token instead");
+                return None;
+            }
+        };
+
         // Hash provided key
         let mut context = Context::new(&SHA256);
         context.update(provided_key.as_bytes());
         let provided_hash = general_purpose::STANDARD.encode(context.finish().as_ref());
-        
-        // Constant-time comparison
-        use std::cmp::Ordering;
-        let is_valid = provided_hash.cmp(&self.key_hash) == Ordering::Equal;
-        
-        if !is_valid {
+
+        let now = chrono::Utc::now();
+        let mut matched_id: Option<String> = None;
+
+        for key in keys {
+            let hashes_equal = provided_hash.len() == key.hash.len()
+                && ring::constant_time::verify_slices_are_equal(
+                    provided_hash.as_bytes(),
+                    key.hash.as_bytes(),
+                )
+                .is_ok();
+            let within_grace = key.status != KeyStatus::Revoked
+                && key.not_after.map(|deadline| now <= deadline).unwrap_or(true);
+
+            if hashes_equal && within_grace {
+                matched_id = Some(key.id.clone());
+            }
+        }
+
+        if matched_id.is_none() {
             warn!("API key validation failed - unauthorized access attempt");
         }
-        
-        is_valid
+
+        matched_id
+    }
+
+    /// Verify a PASETO `v4.public.<payload>.<footer>` token and return its claims.
+    ///
+    /// Parses the four dot-separated components, recomputes the PAE (pre-authentication
+    /// encoding) of the header/payload/footer, and checks the trailing 64-byte Ed25519
+    /// signature against the configured public key before trusting `exp`/`nbf`/`scopes`.
+    pub fn verify_token(&self, token: &str) -> Result<Claims> {
+        let public_key = match &self.auth_mode {
+            AuthMode::Asymmetric { public_key } => public_key,
+            AuthMode::Symmetric { .. } => {
+                return Err(anyhow::anyhow!(
+                    "Token verification requires asymmetric auth mode"
+                ));
+            }
+        };
+
+        let parts: Vec<&str> = token.split('.').collect();
+        if parts.len() != 4 || parts[0] != "v4" || parts[1] != "public" {
+            warn!("Malformed PASETO token rejected");
+            return Err(anyhow::anyhow!("Invalid token format"));
+        }
+
+        let payload_and_sig = general_purpose::URL_SAFE_NO_PAD
+            .decode(parts[2])
+            .map_err(|_| anyhow::anyhow!("Invalid token payload encoding"))?;
+        let footer = if parts[3].is_empty() {
+            Vec::new()
+        } else {
+            general_purpose::URL_SAFE_NO_PAD
+                .decode(parts[3])
+                .map_err(|_| anyhow::anyhow!("Invalid token footer encoding"))?
+        };
+
+        if payload_and_sig.len() <= 64 {
+            warn!("PASETO token rejected - payload too short to contain a signature");
+            return Err(anyhow::anyhow!("Invalid token payload"));
+        }
+        let split_at = payload_and_sig.len() - 64;
+        let (message, signature) = payload_and_sig.split_at(split_at);
+
+        let pae = Self::pre_authentication_encode(&[b"v4.public.", message, &footer]);
+
+        let verifying_key = UnparsedPublicKey::new(&ED25519, public_key.as_slice());
+        verifying_key.verify(&pae, signature).map_err(|_| {
+            warn!("PASETO signature verification failed - rejecting token");
+            anyhow::anyhow!("Invalid token signature")
+        })?;
+
+        let claims: Claims = serde_json::from_slice(message)
+            .map_err(|_| anyhow::anyhow!("Invalid token claims payload"))?;
+
+        let now = chrono::Utc::now();
+        if claims.exp <= now {
+            warn!("Expired PASETO token rejected for subject: {}", claims.sub);
+            return Err(anyhow::anyhow!("Token expired"));
+        }
+        if let Some(nbf) = claims.nbf {
+            if nbf > now {
+                warn!("Not-yet-valid PASETO token rejected for subject: {}", claims.sub);
+                return Err(anyhow::anyhow!("Token not yet valid"));
+            }
+        }
+
+        debug!("PASETO token verified for subject with {} scope(s)", claims.scopes.len());
+        Ok(claims)
+    }
+
+    /// PASETO pre-authentication encoding (PAE): a length-prefixed concatenation of
+    /// each piece, used as the message actually signed/verified.
+    fn pre_authentication_encode(pieces: &[&[u8]]) -> Vec<u8> {
+        let mut output = Vec::new();
+        output.extend_from_slice(&(pieces.len() as u64).to_le_bytes());
+        for piece in pieces {
+            output.extend_from_slice(&(piece.len() as u64).to_le_bytes());
+            output.extend_from_slice(piece);
+        }
+        output
+    }
+}
+
+/// SigV4-style signed-request verifier with replay protection.
+///
+/// Binds a request to its body and a timestamp by recomputing an HMAC-SHA256
+/// signature over a canonical request string, using a signing key derived through
+/// a chain of HMACs (`HMAC(HMAC("BRIDGE" + secret, date), scope)`), mirroring AWS
+/// SigV4's derivation so a leaked long-term secret never signs requests directly.
+pub struct RequestSigner {
+    secret: Secret<String>,
+    max_skew: chrono::Duration,
+    seen_signatures: Arc<DashMap<String, Instant>>,
+    nonce_ttl: Duration,
+}
+
+impl RequestSigner {
+    pub fn new(secret: Secret<String>) -> Self {
+        Self {
+            secret,
+            max_skew: chrono::Duration::minutes(5),
+            seen_signatures: Arc::new(DashMap::new()),
+            nonce_ttl: Duration::from_secs(15 * 60),
+        }
+    }
+
+    /// Verify a signed request against the `x-amz-date`/`x-signature`/`x-signed-headers`
+    /// headers, rejecting stale timestamps, bad signatures, and replays of a
+    /// previously-seen signature within the skew window.
+    pub fn verify(
+        &self,
+        context: &SecurityContext,
+        headers: &HashMap<String, String>,
+        body: &[u8],
+        audit_log: Option<&AuditLog>,
+    ) -> Result<()> {
+        let timestamp_str = headers
+            .get("x-amz-date")
+            .ok_or_else(|| anyhow::anyhow!("Missing x-amz-date header"))?;
+        let timestamp = chrono::DateTime::parse_from_rfc3339(timestamp_str)
+            .map_err(|_| anyhow::anyhow!("Invalid x-amz-date header"))?
+            .with_timezone(&chrono::Utc);
+
+        let skew_seconds = (chrono::Utc::now() - timestamp).num_seconds().abs();
+        if skew_seconds > self.max_skew.num_seconds() {
+            warn!(
+                "Signed request rejected for {}: timestamp skew {}s exceeds window",
+                context.request_id, skew_seconds
+            );
+            return Err(anyhow::anyhow!("Request timestamp outside allowed skew"));
+        }
+
+        let provided_signature = headers
+            .get("x-signature")
+            .ok_or_else(|| anyhow::anyhow!("Missing x-signature header"))?;
+
+        let signed_headers: Vec<String> = headers
+            .get("x-signed-headers")
+            .map(|s| s.split(';').map(|h| h.to_lowercase()).collect())
+            .unwrap_or_default();
+
+        let canonical_request = self.canonical_request(context, headers, &signed_headers, body);
+        let date_stamp = timestamp.format("%Y%m%d").to_string();
+        let scope = format!("{}/bridge_request", date_stamp);
+        let expected_signature = self.sign(&date_stamp, &scope, &canonical_request);
+
+        let is_valid = provided_signature.len() == expected_signature.len()
+            && ring::constant_time::verify_slices_are_equal(
+                provided_signature.as_bytes(),
+                expected_signature.as_bytes(),
+            )
+            .is_ok();
+
+        if !is_valid {
+            warn!("Signed request rejected for {}: signature mismatch", context.request_id);
+            return Err(anyhow::anyhow!("Invalid request signature"));
+        }
+
+        // The signature is unique per canonical request + timestamp, so it can
+        // double as the replay nonce within the skew window. `insert` itself is
+        // the check: a prior entry means `insert` returns `Some`, atomically
+        // performing the check-and-mark so two concurrent requests carrying the
+        // same signature can't both observe "not seen yet".
+        if self
+            .seen_signatures
+            .insert(provided_signature.clone(), Instant::now())
+            .is_some()
+        {
+            warn!("Signed request rejected for {}: replay detected", context.request_id);
+            return Err(anyhow::anyhow!("Request already processed"));
+        }
+        self.cleanup_expired_signatures();
+
+        let details = format!("scope={}", scope);
+        match audit_log {
+            Some(log) => context.log_security_event_chained(log, "request_signature_verified", &details),
+            None => context.log_security_event("request_signature_verified", &details),
+        }
+        Ok(())
+    }
+
+    fn canonical_request(
+        &self,
+        context: &SecurityContext,
+        headers: &HashMap<String, String>,
+        signed_headers: &[String],
+        body: &[u8],
+    ) -> String {
+        let (path, query) = context.path.split_once('?').unwrap_or((&context.path, ""));
+
+        let mut query_pairs: Vec<&str> = query.split('&').filter(|s| !s.is_empty()).collect();
+        query_pairs.sort_unstable();
+        let canonical_query = query_pairs.join("&");
+
+        let mut canonical_headers = String::new();
+        for name in signed_headers {
+            if let Some(value) = headers.get(name) {
+                canonical_headers.push_str(&format!("{}:{}\n", name, value.trim()));
+            }
+        }
+
+        let mut hasher = Sha2Sha256::new();
+        hasher.update(body);
+        let body_hash = Self::to_hex(&hasher.finalize());
+
+        format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            context.method,
+            path,
+            canonical_query,
+            canonical_headers,
+            signed_headers.join(";"),
+            body_hash
+        )
+    }
+
+    fn derive_signing_key(&self, date_stamp: &str, scope: &str) -> Vec<u8> {
+        let seed = format!("BRIDGE{}", self.secret.expose_secret());
+        let k_date = Self::hmac(seed.as_bytes(), date_stamp.as_bytes());
+        Self::hmac(&k_date, scope.as_bytes())
+    }
+
+    fn sign(&self, date_stamp: &str, scope: &str, canonical_request: &str) -> String {
+        let signing_key = self.derive_signing_key(date_stamp, scope);
+        Self::to_hex(&Self::hmac(&signing_key, canonical_request.as_bytes()))
+    }
+
+    fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts a key of any size");
+        mac.update(data);
+        mac.finalize().into_bytes().to_vec()
+    }
+
+    fn to_hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    /// Evict signatures outside the replay window so memory stays bounded.
+    fn cleanup_expired_signatures(&self) {
+        let cutoff = Instant::now() - self.nonce_ttl;
+        self.seen_signatures.retain(|_, seen_at| *seen_at > cutoff);
+    }
+}
+
+/// Default "Have I Been Pwned" range-query endpoint, queried via k-anonymity so the
+/// full secret never leaves the process.
+const DEFAULT_HIBP_RANGE_URL: &str = "https://api.pwnedpasswords.com/range";
+
+/// Screens API keys/passwords against a public breach corpus without ever
+/// transmitting the full secret: only a 5-character SHA-1 prefix is sent, and the
+/// full hash is matched locally against the returned suffix list.
+pub struct CredentialScreener {
+    client: reqwest::Client,
+    range_url_base: String,
+    /// Occurrence count at or above which a secret is treated as compromised.
+    threshold: u64,
+}
+
+impl CredentialScreener {
+    pub fn new() -> Self {
+        Self::with_base_url(DEFAULT_HIBP_RANGE_URL.to_string())
+    }
+
+    /// Point the screener at a self-hosted mirror (or a test stub) instead of the
+    /// public HIBP range endpoint.
+    pub fn with_base_url(range_url_base: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            range_url_base,
+            threshold: 1,
+        }
+    }
+
+    pub fn with_threshold(mut self, threshold: u64) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Check `secret` against the breach corpus using the k-anonymity protocol:
+    /// hash the secret, send only the first 5 hex characters, and scan the
+    /// `SUFFIX:COUNT` response locally for a match against the remaining 35.
+    pub async fn is_compromised(&self, secret: &Secret<String>) -> Result<Option<u64>> {
+        let mut hasher = Sha1::new();
+        hasher.update(secret.expose_secret().as_bytes());
+        let digest = hasher.finalize();
+        let hex_digest: String = digest.iter().map(|b| format!("{:02X}", b)).collect();
+        let (prefix, suffix) = hex_digest.split_at(5);
+
+        let url = format!("{}/{}", self.range_url_base, prefix);
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| anyhow::anyhow!("Credential screening request failed: {}", e))?;
+
+        if !response.status().is_success() {
+            warn!(
+                "Credential screening endpoint returned {} - failing open",
+                response.status()
+            );
+            return Ok(None);
+        }
+
+        let body = response
+            .text()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to read credential screening response: {}", e))?;
+
+        let count = Self::find_count_in_range_response(&body, suffix, self.threshold);
+        if let Some(count) = count {
+            debug!("Credential screening match found, occurrence count: {}", count);
+        }
+        Ok(count)
+    }
+
+    /// Scan a newline-delimited `SUFFIX:COUNT` range response for `suffix`,
+    /// returning its count when present and at or above `threshold`.
+    fn find_count_in_range_response(body: &str, suffix: &str, threshold: u64) -> Option<u64> {
+        for line in body.lines() {
+            if let Some((candidate_suffix, count_str)) = line.trim().split_once(':') {
+                if candidate_suffix.eq_ignore_ascii_case(suffix) {
+                    let count: u64 = count_str.trim().parse().unwrap_or(0);
+                    if count >= threshold {
+                        return Some(count);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Per-path override of the default token-bucket capacity/refill rate.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct BucketOverride {
+    pub capacity: u32,
+    pub refill_per_sec: u32,
+}
+
+/// Seconds the caller must wait before a depleted bucket yields another token.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryAfter {
+    pub seconds: u64,
+}
+
+struct TokenBucket {
+    tokens: f64,
+    capacity: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+    last_access: Instant,
+}
+
+impl TokenBucket {
+    fn new(capacity: u32, refill_per_sec: u32) -> Self {
+        let now = Instant::now();
+        Self {
+            tokens: capacity as f64,
+            capacity: capacity as f64,
+            refill_per_sec: refill_per_sec.max(1) as f64,
+            last_refill: now,
+            last_access: now,
+        }
+    }
+
+    fn try_consume(&mut self) -> Result<(), RetryAfter> {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = now;
+        self.last_access = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            let deficit = 1.0 - self.tokens;
+            let seconds = (deficit / self.refill_per_sec).ceil().max(1.0) as u64;
+            Err(RetryAfter { seconds })
+        }
+    }
+}
+
+/// Token-bucket rate limiter backing `SecurityConfig::rate_limit_enabled`.
+///
+/// Buckets are keyed by `(client, path)`, where the client half is
+/// `SecurityContext::client_ip`, falling back to `request_id` when no IP is
+/// available, and held in a sharded `DashMap` so concurrent clients don't
+/// contend on a single lock. Keying by path too (rather than just client)
+/// means a client's bucket for a path with a `path_overrides` entry never
+/// gets mixed up with its bucket for a path without one. A background sweep
+/// evicts buckets that haven't been touched recently, bounding memory growth.
+pub struct RateLimiter {
+    enabled: bool,
+    default_capacity: u32,
+    default_refill_per_sec: u32,
+    path_overrides: HashMap<String, BucketOverride>,
+    buckets: Arc<DashMap<(String, String), Mutex<TokenBucket>>>,
+}
+
+impl RateLimiter {
+    pub fn new(security_config: &SecurityConfig) -> Self {
+        Self {
+            enabled: security_config.rate_limit_enabled,
+            default_capacity: security_config.default_bucket_capacity,
+            default_refill_per_sec: security_config.default_refill_per_sec,
+            path_overrides: security_config.path_bucket_overrides.clone(),
+            buckets: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// Consume a token for this request's client, rejecting with the number of
+    /// seconds until the next token when the bucket is empty.
+    pub fn check(&self, context: &SecurityContext, audit_log: Option<&AuditLog>) -> Result<(), RetryAfter> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        let key = context
+            .client_ip
+            .clone()
+            .unwrap_or_else(|| context.request_id.clone());
+
+        let BucketOverride {
+            capacity,
+            refill_per_sec,
+        } = self
+            .path_overrides
+            .get(&context.path)
+            .copied()
+            .unwrap_or(BucketOverride {
+                capacity: self.default_capacity,
+                refill_per_sec: self.default_refill_per_sec,
+            });
+
+        let bucket_entry = self
+            .buckets
+            .entry((key.clone(), context.path.clone()))
+            .or_insert_with(|| Mutex::new(TokenBucket::new(capacity, refill_per_sec)));
+
+        let mut bucket = bucket_entry
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        match bucket.try_consume() {
+            Ok(()) => Ok(()),
+            Err(retry_after) => {
+                let details = format!("key={} retry_after_seconds={}", key, retry_after.seconds);
+                match audit_log {
+                    Some(log) => context.log_security_event_chained(log, "rate_limited", &details),
+                    None => context.log_security_event("rate_limited", &details),
+                }
+                Err(retry_after)
+            }
+        }
+    }
+
+    /// Evict buckets that have not been used within `idle_for`, bounding the
+    /// map's memory footprint. Intended to be called periodically from a
+    /// background task.
+    pub async fn sweep_idle_entries(&self, idle_for: Duration) {
+        let cutoff = Instant::now() - idle_for;
+        self.buckets.retain(|_, bucket| {
+            bucket
+                .lock()
+                .map(|b| b.last_access > cutoff)
+                .unwrap_or(true)
+        });
+        debug!("Rate limiter sweep complete, {} active buckets", self.buckets.len());
     }
 }
 
@@ -157,6 +827,189 @@ impl InputSanitizer {
     }
 }
 
+/// A single condition inside an upload policy document. `Eq`/`StartsWith` are
+/// matched against a submitted form field by name; `ContentLengthRange` is
+/// matched against the size of the uploaded file itself.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "condition", rename_all = "kebab-case")]
+pub enum PolicyCondition {
+    Eq { field: String, value: String },
+    StartsWith { field: String, prefix: String },
+    ContentLengthRange { min: u64, max: u64 },
+}
+
+/// Signed upload policy document, submitted base64-encoded as the `policy`
+/// form field alongside the file and the fields it constrains.
+#[derive(Debug, Clone, Deserialize)]
+pub struct UploadPolicy {
+    pub expiration: chrono::DateTime<chrono::Utc>,
+    pub conditions: Vec<PolicyCondition>,
+}
+
+/// A multipart upload that satisfied every condition in its embedded policy.
+#[derive(Debug, Clone)]
+pub struct ValidatedUpload {
+    pub object_key: String,
+    pub content_length: u64,
+    pub fields: HashMap<String, String>,
+}
+
+/// Validates `multipart/form-data` uploads against the security policy embedded
+/// in the request itself: the policy must carry a valid HMAC-SHA256 signature
+/// over its base64 form (so a client can't forge or alter it), every submitted
+/// field must satisfy its matching condition, no field outside the policy may
+/// be present, and the policy must not have expired.
+pub struct FormPolicyValidator {
+    input_sanitizer: InputSanitizer,
+    error_handler: SecureErrorHandler,
+    policy_secret: Secret<String>,
+}
+
+impl FormPolicyValidator {
+    pub fn new(input_sanitizer: InputSanitizer, error_handler: SecureErrorHandler, policy_secret: Secret<String>) -> Self {
+        Self {
+            input_sanitizer,
+            error_handler,
+            policy_secret,
+        }
+    }
+
+    /// Sign an already base64-encoded policy document, producing the value a
+    /// client must submit in the `signature` form field alongside `policy`.
+    pub fn sign_policy(&self, policy_b64: &str) -> String {
+        Self::to_hex(&Self::hmac(self.policy_secret.expose_secret().as_bytes(), policy_b64.as_bytes()))
+    }
+
+    fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts a key of any size");
+        mac.update(data);
+        mac.finalize().into_bytes().to_vec()
+    }
+
+    fn to_hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    /// Parse the multipart `body` (boundary taken from `content_type`) and
+    /// validate it against its embedded policy. Returns a structured rejection
+    /// through `SecureErrorHandler` on failure, so production responses don't
+    /// leak which specific condition failed.
+    pub async fn validate_upload(
+        &self,
+        content_type: &str,
+        body: bytes::Bytes,
+    ) -> std::result::Result<ValidatedUpload, serde_json::Value> {
+        self.validate_upload_inner(content_type, body)
+            .await
+            .map_err(|e| self.error_handler.sanitize_error(&e, "form_policy_validation"))
+    }
+
+    async fn validate_upload_inner(&self, content_type: &str, body: bytes::Bytes) -> Result<ValidatedUpload> {
+        let boundary = multer::parse_boundary(content_type)
+            .map_err(|_| anyhow::anyhow!("Missing or invalid multipart boundary"))?;
+        let stream = futures::stream::once(async move { Ok::<_, std::io::Error>(body) });
+        let mut multipart = multer::Multipart::new(stream, boundary);
+
+        let mut fields: HashMap<String, String> = HashMap::new();
+        let mut object_key: Option<String> = None;
+        let mut content_length: u64 = 0;
+
+        while let Some(field) = multipart.next_field().await? {
+            let name = field.name().unwrap_or_default().to_string();
+            if name == "file" {
+                content_length = field.bytes().await?.len() as u64;
+                continue;
+            }
+
+            let value = field.text().await?;
+            if name == "key" {
+                object_key = Some(self.input_sanitizer.validate_file_path(&value)?);
+            } else {
+                fields.insert(name.clone(), self.input_sanitizer.sanitize_string(&value, &name)?);
+            }
+        }
+
+        let object_key = object_key.ok_or_else(|| anyhow::anyhow!("Missing key field"))?;
+        let policy_b64 = fields
+            .remove("policy")
+            .ok_or_else(|| anyhow::anyhow!("Missing policy field"))?;
+        let provided_signature = fields
+            .remove("signature")
+            .ok_or_else(|| anyhow::anyhow!("Missing signature field"))?;
+
+        let expected_signature = self.sign_policy(&policy_b64);
+        let is_valid = provided_signature.len() == expected_signature.len()
+            && ring::constant_time::verify_slices_are_equal(
+                provided_signature.as_bytes(),
+                expected_signature.as_bytes(),
+            )
+            .is_ok();
+        if !is_valid {
+            return Err(anyhow::anyhow!("Invalid policy signature"));
+        }
+
+        let policy_json = general_purpose::STANDARD
+            .decode(policy_b64.as_bytes())
+            .map_err(|_| anyhow::anyhow!("Policy is not valid base64"))?;
+        let policy: UploadPolicy = serde_json::from_slice(&policy_json)
+            .map_err(|_| anyhow::anyhow!("Policy document is malformed"))?;
+
+        if chrono::Utc::now() > policy.expiration {
+            return Err(anyhow::anyhow!("Upload policy has expired"));
+        }
+
+        let mut condition_fields: std::collections::HashSet<String> = std::collections::HashSet::new();
+        for condition in &policy.conditions {
+            let satisfied = match condition {
+                PolicyCondition::Eq { field, value } => {
+                    condition_fields.insert(field.clone());
+                    field_value(field, &object_key, &fields)? == *value
+                }
+                PolicyCondition::StartsWith { field, prefix } => {
+                    condition_fields.insert(field.clone());
+                    field_value(field, &object_key, &fields)?.starts_with(prefix.as_str())
+                }
+                PolicyCondition::ContentLengthRange { min, max } => {
+                    content_length >= *min && content_length <= *max
+                }
+            };
+            if !satisfied {
+                return Err(anyhow::anyhow!("Upload does not satisfy policy condition"));
+            }
+        }
+
+        // Every submitted field must be covered by some condition - fields the
+        // policy doesn't mention are rejected outright rather than ignored.
+        for field_name in fields.keys() {
+            if !condition_fields.contains(field_name) {
+                return Err(anyhow::anyhow!("Unexpected field '{}' not covered by policy", field_name));
+            }
+        }
+
+        Ok(ValidatedUpload {
+            object_key,
+            content_length,
+            fields,
+        })
+    }
+}
+
+/// Resolve `field` against the reserved `key` field or the submitted field map.
+fn field_value<'a>(
+    field: &str,
+    object_key: &'a str,
+    fields: &'a HashMap<String, String>,
+) -> Result<&'a str> {
+    if field == "key" {
+        Ok(object_key)
+    } else {
+        fields
+            .get(field)
+            .map(|s| s.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing required field '{}'", field))
+    }
+}
+
 /// Secure error handler - NEVER leaks sensitive information
 pub struct SecureErrorHandler {
     include_details: bool,
@@ -190,7 +1043,7 @@ impl SecureErrorHandler {
         }
     }
     
-    fn sanitize_error_message(&self, message: &str) -> String {
+    pub fn sanitize_error_message(&self, message: &str) -> String {
         // Remove potentially sensitive information from error messages
         let sensitive_patterns = [
             r"api[_-]?key[s]?[:\s=]+[^\s]+",
@@ -249,6 +1102,119 @@ impl SecurityContext {
             details
         );
     }
+
+    /// Like [`SecurityContext::log_security_event`], but also appends a
+    /// tamper-evident record to `audit_log` so the event survives even if the
+    /// tracing line is edited or dropped.
+    pub fn log_security_event_chained(&self, audit_log: &AuditLog, event_type: &str, details: &str) {
+        self.log_security_event(event_type, details);
+        audit_log.append(self, event_type, details);
+    }
+}
+
+/// A single append-only audit record. Each record embeds the SHA256 digest of
+/// the previous record's canonical bytes, so an edit or deletion anywhere in
+/// the chain is detectable by [`AuditLog::verify`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub index: u64,
+    pub request_id: String,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub event_type: String,
+    pub authenticated: bool,
+    pub details: String,
+    pub prev_hash: String,
+}
+
+impl AuditRecord {
+    /// SHA256 digest of this record's canonical (fixed-order) bytes, used as the
+    /// `prev_hash` embedded in the next record appended to the chain.
+    fn canonical_hash(&self) -> String {
+        let canonical = format!(
+            "{}|{}|{}|{}|{}|{}|{}",
+            self.index,
+            self.request_id,
+            self.timestamp.to_rfc3339(),
+            self.event_type,
+            self.authenticated,
+            self.details,
+            self.prev_hash
+        );
+        let mut context = Context::new(&SHA256);
+        context.update(canonical.as_bytes());
+        general_purpose::STANDARD.encode(context.finish().as_ref())
+    }
+}
+
+/// Hash anchoring an empty chain - the `prev_hash` of the first appended record.
+const AUDIT_GENESIS_HASH: &str = "GENESIS";
+
+/// Tamper-evident audit log backing `SecurityConfig::audit_logging_enabled`.
+///
+/// Appends structured, redacted JSON records to an in-memory append-only sink,
+/// hash-chaining each one to its predecessor so downstream edits or deletions
+/// are detectable rather than silently accepted.
+pub struct AuditLog {
+    records: Mutex<Vec<AuditRecord>>,
+    error_handler: SecureErrorHandler,
+}
+
+impl AuditLog {
+    pub fn new() -> Self {
+        Self {
+            records: Mutex::new(Vec::new()),
+            error_handler: SecureErrorHandler::new(true),
+        }
+    }
+
+    /// Append a record for `event_type`/`details`, redacting `details` through
+    /// `SecureErrorHandler::sanitize_error_message` before it enters the chain.
+    pub fn append(&self, context: &SecurityContext, event_type: &str, details: &str) -> AuditRecord {
+        let sanitized_details = self.error_handler.sanitize_error_message(details);
+        let mut records = self.records.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        let prev_hash = records
+            .last()
+            .map(AuditRecord::canonical_hash)
+            .unwrap_or_else(|| AUDIT_GENESIS_HASH.to_string());
+
+        let record = AuditRecord {
+            index: records.len() as u64,
+            request_id: context.request_id.clone(),
+            timestamp: chrono::Utc::now(),
+            event_type: event_type.to_string(),
+            authenticated: context.authenticated,
+            details: sanitized_details,
+            prev_hash,
+        };
+        records.push(record.clone());
+        record
+    }
+
+    /// Walk the chain and confirm every record's stored `prev_hash` matches the
+    /// recomputed digest of its predecessor. Returns an error naming the index
+    /// of the first corrupted link, or `Ok` if the chain is intact.
+    pub fn verify(&self) -> Result<()> {
+        let records = self.records.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut expected_prev = AUDIT_GENESIS_HASH.to_string();
+
+        for record in records.iter() {
+            if record.prev_hash != expected_prev {
+                return Err(anyhow::anyhow!(
+                    "audit log tampering detected at index {}: prev-hash mismatch",
+                    record.index
+                ));
+            }
+            expected_prev = record.canonical_hash();
+        }
+        Ok(())
+    }
+}
+
+impl Default for AuditLog {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[cfg(test)]
@@ -290,11 +1256,403 @@ mod tests {
     async fn test_api_key_manager() {
         let api_key = Secret::new("test_api_key_12345678901234567890".to_string());
         let manager = ApiKeyManager::new(&api_key).unwrap();
-        
-        // Valid key should pass
-        assert!(manager.validate_key("test_api_key_12345678901234567890"));
-        
+
+        // Valid key should pass and report the matching key's id
+        assert!(manager.validate_key("test_api_key_12345678901234567890").is_some());
+
         // Invalid key should fail
-        assert!(!manager.validate_key("wrong_key"));
+        assert!(manager.validate_key("wrong_key").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_rotate_in_keeps_old_key_valid_during_grace_period() {
+        let api_key = Secret::new("test_api_key_12345678901234567890".to_string());
+        let mut manager = ApiKeyManager::new(&api_key).unwrap();
+
+        let new_key = Secret::new("rotated_api_key_98765432109876543".to_string());
+        let new_id = manager.rotate_in(&new_key, chrono::Duration::minutes(5)).unwrap();
+
+        // Old key still validates (deprecated, within grace period)
+        assert!(manager.validate_key("test_api_key_12345678901234567890").is_some());
+        // New key validates and reports its own id
+        assert_eq!(manager.validate_key("rotated_api_key_98765432109876543"), Some(new_id));
+    }
+
+    #[tokio::test]
+    async fn test_revoke_invalidates_key_immediately() {
+        let api_key = Secret::new("test_api_key_12345678901234567890".to_string());
+        let mut manager = ApiKeyManager::new(&api_key).unwrap();
+
+        let key_id = manager.validate_key("test_api_key_12345678901234567890").unwrap();
+        manager.revoke(&key_id).unwrap();
+
+        assert!(manager.validate_key("test_api_key_12345678901234567890").is_none());
+    }
+
+    #[test]
+    fn test_paseto_token_roundtrip() {
+        use ring::rand::SystemRandom;
+        use ring::signature::{Ed25519KeyPair, KeyPair};
+
+        let rng = SystemRandom::new();
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+        let key_pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+
+        let claims = serde_json::json!({
+            "sub": "client-1",
+            "exp": (chrono::Utc::now() + chrono::Duration::hours(1)).to_rfc3339(),
+            "scopes": ["read", "write"]
+        });
+        let message = serde_json::to_vec(&claims).unwrap();
+        let footer: &[u8] = b"";
+        let pae = ApiKeyManager::pre_authentication_encode(&[b"v4.public.", &message, footer]);
+        let signature = key_pair.sign(&pae);
+
+        let mut payload_and_sig = message.clone();
+        payload_and_sig.extend_from_slice(signature.as_ref());
+
+        let token = format!(
+            "v4.public.{}.",
+            general_purpose::URL_SAFE_NO_PAD.encode(&payload_and_sig)
+        );
+
+        let manager = ApiKeyManager::new_asymmetric(key_pair.public_key().as_ref()).unwrap();
+        let decoded = manager.verify_token(&token).unwrap();
+        assert_eq!(decoded.sub, "client-1");
+        assert_eq!(decoded.scopes, vec!["read".to_string(), "write".to_string()]);
+    }
+
+    #[test]
+    fn test_paseto_rejects_tampered_signature() {
+        use ring::rand::SystemRandom;
+        use ring::signature::{Ed25519KeyPair, KeyPair};
+
+        let rng = SystemRandom::new();
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+        let key_pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+
+        let claims = serde_json::json!({
+            "sub": "client-1",
+            "exp": (chrono::Utc::now() + chrono::Duration::hours(1)).to_rfc3339(),
+        });
+        let message = serde_json::to_vec(&claims).unwrap();
+        let mut payload_and_sig = message.clone();
+        payload_and_sig.extend_from_slice(&[0u8; 64]); // bogus signature
+
+        let token = format!(
+            "v4.public.{}.",
+            general_purpose::URL_SAFE_NO_PAD.encode(&payload_and_sig)
+        );
+
+        let manager = ApiKeyManager::new_asymmetric(key_pair.public_key().as_ref()).unwrap();
+        assert!(manager.verify_token(&token).is_err());
+    }
+
+    fn signed_headers_map(date: &str) -> HashMap<String, String> {
+        let mut headers = HashMap::new();
+        headers.insert("host".to_string(), "bridge.internal".to_string());
+        headers.insert("x-amz-date".to_string(), date.to_string());
+        headers.insert("x-signed-headers".to_string(), "host".to_string());
+        headers
+    }
+
+    #[test]
+    fn test_request_signer_accepts_valid_signature() {
+        let signer = RequestSigner::new(Secret::new("shared-signing-secret".to_string()));
+        let context = SecurityContext::new("POST".to_string(), "/mcp".to_string());
+        let now = chrono::Utc::now().to_rfc3339();
+        let mut headers = signed_headers_map(&now);
+        let body = b"{\"jsonrpc\":\"2.0\"}";
+
+        let date_stamp = chrono::Utc::now().format("%Y%m%d").to_string();
+        let scope = format!("{}/bridge_request", date_stamp);
+        let canonical = signer.canonical_request(
+            &context,
+            &headers,
+            &["host".to_string()],
+            body,
+        );
+        let signature = signer.sign(&date_stamp, &scope, &canonical);
+        headers.insert("x-signature".to_string(), signature);
+
+        assert!(signer.verify(&context, &headers, body, None).is_ok());
+    }
+
+    #[test]
+    fn test_request_signer_rejects_replay() {
+        let signer = RequestSigner::new(Secret::new("shared-signing-secret".to_string()));
+        let context = SecurityContext::new("POST".to_string(), "/mcp".to_string());
+        let now = chrono::Utc::now().to_rfc3339();
+        let mut headers = signed_headers_map(&now);
+        let body = b"{\"jsonrpc\":\"2.0\"}";
+
+        let date_stamp = chrono::Utc::now().format("%Y%m%d").to_string();
+        let scope = format!("{}/bridge_request", date_stamp);
+        let canonical = signer.canonical_request(&context, &headers, &["host".to_string()], body);
+        let signature = signer.sign(&date_stamp, &scope, &canonical);
+        headers.insert("x-signature".to_string(), signature);
+
+        assert!(signer.verify(&context, &headers, body, None).is_ok());
+        // Second presentation of the same signature must be rejected as a replay.
+        assert!(signer.verify(&context, &headers, body, None).is_err());
+    }
+
+    #[test]
+    fn test_request_signer_rejects_stale_timestamp() {
+        let signer = RequestSigner::new(Secret::new("shared-signing-secret".to_string()));
+        let context = SecurityContext::new("POST".to_string(), "/mcp".to_string());
+        let stale = (chrono::Utc::now() - chrono::Duration::minutes(10)).to_rfc3339();
+        let headers = signed_headers_map(&stale);
+        let body = b"{}";
+
+        assert!(signer.verify(&context, &headers, body, None).is_err());
+    }
+
+    #[test]
+    fn test_credential_screener_range_parsing() {
+        let body = "003D68EB55068C33ACE09247EE4C639306:5\r\n0018A45C4D1DEF81644B54AB7F969B88D65:3730471\r\n";
+        assert_eq!(
+            CredentialScreener::find_count_in_range_response(
+                body,
+                "0018A45C4D1DEF81644B54AB7F969B88D65",
+                1
+            ),
+            Some(3730471)
+        );
+        assert_eq!(
+            CredentialScreener::find_count_in_range_response(body, "NOTPRESENT", 1),
+            None
+        );
+    }
+
+    #[test]
+    fn test_rate_limiter_token_bucket() {
+        let mut config = SecurityConfig::new();
+        config.default_bucket_capacity = 2;
+        config.default_refill_per_sec = 1;
+        let limiter = RateLimiter::new(&config);
+
+        let mut context = SecurityContext::new("GET".to_string(), "/mcp".to_string());
+        context.client_ip = Some("127.0.0.1".to_string());
+
+        assert!(limiter.check(&context, None).is_ok());
+        assert!(limiter.check(&context, None).is_ok());
+        // Bucket now empty - third request in the same instant must be rejected.
+        assert!(limiter.check(&context, None).is_err());
+    }
+
+    #[test]
+    fn test_rate_limiter_path_override_does_not_leak_to_other_paths() {
+        let mut config = SecurityConfig::new();
+        config.default_bucket_capacity = 1;
+        config.default_refill_per_sec = 1;
+        config.path_bucket_overrides.insert(
+            "/expensive".to_string(),
+            BucketOverride { capacity: 5, refill_per_sec: 1 },
+        );
+        let limiter = RateLimiter::new(&config);
+
+        let mut overridden = SecurityContext::new("GET".to_string(), "/expensive".to_string());
+        overridden.client_ip = Some("127.0.0.1".to_string());
+        // Same client, default-capacity path - must still use the default
+        // capacity of 1, not the 5 the client's other bucket was given.
+        let mut plain = SecurityContext::new("GET".to_string(), "/mcp".to_string());
+        plain.client_ip = Some("127.0.0.1".to_string());
+
+        for _ in 0..5 {
+            assert!(limiter.check(&overridden, None).is_ok());
+        }
+        assert!(limiter.check(&overridden, None).is_err());
+
+        assert!(limiter.check(&plain, None).is_ok());
+        assert!(limiter.check(&plain, None).is_err());
+    }
+
+    #[test]
+    fn test_rate_limiter_disabled_allows_everything() {
+        let mut config = SecurityConfig::new();
+        config.rate_limit_enabled = false;
+        config.default_bucket_capacity = 1;
+        let limiter = RateLimiter::new(&config);
+        let context = SecurityContext::new("GET".to_string(), "/mcp".to_string());
+
+        for _ in 0..10 {
+            assert!(limiter.check(&context, None).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_audit_log_chain_verifies_when_untampered() {
+        let audit_log = AuditLog::new();
+        let context = SecurityContext::new("POST".to_string(), "/mcp".to_string());
+
+        audit_log.append(&context, "request_signature_verified", "scope=20260101/bridge_request");
+        audit_log.append(&context, "rate_limited", "key=127.0.0.1 retry_after_seconds=3");
+
+        assert!(audit_log.verify().is_ok());
+    }
+
+    #[test]
+    fn test_audit_log_detects_tampering() {
+        let audit_log = AuditLog::new();
+        let context = SecurityContext::new("POST".to_string(), "/mcp".to_string());
+
+        audit_log.append(&context, "request_signature_verified", "scope=20260101/bridge_request");
+        audit_log.append(&context, "rate_limited", "key=127.0.0.1 retry_after_seconds=3");
+
+        {
+            let mut records = audit_log.records.lock().unwrap();
+            records[0].details = "scope=tampered".to_string();
+        }
+
+        let err = audit_log.verify().unwrap_err();
+        assert!(err.to_string().contains("index 1"));
+    }
+
+    #[test]
+    fn test_audit_log_redacts_sensitive_details() {
+        let audit_log = AuditLog::new();
+        let context = SecurityContext::new("POST".to_string(), "/mcp".to_string());
+
+        let record = audit_log.append(&context, "auth_failed", "api_key: sk-super-secret-value");
+        assert!(!record.details.contains("sk-super-secret-value"));
+        assert!(record.details.contains("[REDACTED]"));
+    }
+
+    fn build_multipart_body(boundary: &str, fields: &[(&str, &str)], file_contents: &[u8]) -> bytes::Bytes {
+        let mut body = Vec::new();
+        for (name, value) in fields {
+            body.extend_from_slice(format!(
+                "--{boundary}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n{value}\r\n"
+            ).as_bytes());
+        }
+        body.extend_from_slice(format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"upload.bin\"\r\n\r\n"
+        ).as_bytes());
+        body.extend_from_slice(file_contents);
+        body.extend_from_slice(b"\r\n");
+        body.extend_from_slice(format!("--{boundary}--\r\n").as_bytes());
+        bytes::Bytes::from(body)
+    }
+
+    fn encode_policy(policy: &serde_json::Value) -> String {
+        general_purpose::STANDARD.encode(policy.to_string().as_bytes())
+    }
+
+    fn test_validator() -> FormPolicyValidator {
+        FormPolicyValidator::new(
+            InputSanitizer::new().unwrap(),
+            SecureErrorHandler::new(true),
+            Secret::new("policy-signing-secret".to_string()),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_form_policy_validator_accepts_compliant_upload() {
+        let validator = test_validator();
+        let policy = encode_policy(&serde_json::json!({
+            "expiration": (chrono::Utc::now() + chrono::Duration::hours(1)).to_rfc3339(),
+            "conditions": [
+                {"condition": "starts-with", "field": "key", "prefix": "uploads/"},
+                {"condition": "eq", "field": "content-type", "value": "image/png"},
+                {"condition": "content-length-range", "min": 1, "max": 1024}
+            ]
+        }));
+        let signature = validator.sign_policy(&policy);
+
+        let boundary = "X-TEST-BOUNDARY";
+        let body = build_multipart_body(
+            boundary,
+            &[
+                ("key", "uploads/avatar.png"),
+                ("content-type", "image/png"),
+                ("policy", &policy),
+                ("signature", &signature),
+            ],
+            b"fake-png-bytes",
+        );
+
+        let result = validator
+            .validate_upload(&format!("multipart/form-data; boundary={boundary}"), body)
+            .await
+            .unwrap();
+
+        assert_eq!(result.object_key, "uploads/avatar.png");
+        assert_eq!(result.content_length, "fake-png-bytes".len() as u64);
+    }
+
+    #[tokio::test]
+    async fn test_form_policy_validator_rejects_unmet_condition() {
+        let validator = test_validator();
+        let policy = encode_policy(&serde_json::json!({
+            "expiration": (chrono::Utc::now() + chrono::Duration::hours(1)).to_rfc3339(),
+            "conditions": [
+                {"condition": "eq", "field": "content-type", "value": "image/png"}
+            ]
+        }));
+        let signature = validator.sign_policy(&policy);
+
+        let boundary = "X-TEST-BOUNDARY";
+        let body = build_multipart_body(
+            boundary,
+            &[
+                ("key", "uploads/avatar.png"),
+                ("content-type", "application/x-executable"),
+                ("policy", &policy),
+                ("signature", &signature),
+            ],
+            b"fake-bytes",
+        );
+
+        let result = validator
+            .validate_upload(&format!("multipart/form-data; boundary={boundary}"), body)
+            .await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_form_policy_validator_rejects_expired_policy() {
+        let validator = test_validator();
+        let policy = encode_policy(&serde_json::json!({
+            "expiration": (chrono::Utc::now() - chrono::Duration::hours(1)).to_rfc3339(),
+            "conditions": []
+        }));
+        let signature = validator.sign_policy(&policy);
+
+        let boundary = "X-TEST-BOUNDARY";
+        let body = build_multipart_body(
+            boundary,
+            &[("key", "uploads/avatar.png"), ("policy", &policy), ("signature", &signature)],
+            b"x",
+        );
+
+        let result = validator
+            .validate_upload(&format!("multipart/form-data; boundary={boundary}"), body)
+            .await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_form_policy_validator_rejects_forged_policy_signature() {
+        let validator = test_validator();
+        let policy = encode_policy(&serde_json::json!({
+            "expiration": (chrono::Utc::now() + chrono::Duration::hours(1)).to_rfc3339(),
+            "conditions": []
+        }));
+
+        let boundary = "X-TEST-BOUNDARY";
+        let body = build_multipart_body(
+            boundary,
+            &[("key", "uploads/avatar.png"), ("policy", &policy), ("signature", "deadbeef")],
+            b"x",
+        );
+
+        let result = validator
+            .validate_upload(&format!("multipart/form-data; boundary={boundary}"), body)
+            .await;
+
+        assert!(result.is_err());
     }
 }
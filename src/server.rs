@@ -17,7 +17,7 @@ use crate::{
     health::HealthChecker,
     mcp::McpHandler,
     deepseek::DeepSeekClient,
-    metrics::MetricsCollector,
+    metrics::{MetricLabels, MetricsCollector},
 };
 
 pub struct Server {
@@ -129,16 +129,16 @@ async fn mcp_handler(
     State(state): State<AppState>,
     Json(request): Json<serde_json::Value>,
 ) -> impl IntoResponse {
-    state.metrics.increment_request_count("mcp").await;
-    
+    state.metrics.increment_request_count(MetricLabels::new("mcp")).await;
+
     match state.mcp_handler.handle_request(request).await {
         Ok(response) => {
-            state.metrics.increment_success_count("mcp").await;
+            state.metrics.increment_success_count(MetricLabels::new("mcp")).await;
             (StatusCode::OK, Json(response)).into_response()
         }
         Err(e) => {
             error!("MCP handler error: {}", e);
-            state.metrics.increment_error_count("mcp").await;
+            state.metrics.increment_error_count(MetricLabels::new("mcp")).await;
             (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({
                 "error": e.to_string()
             }))).into_response()
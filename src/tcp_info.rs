@@ -0,0 +1,66 @@
+//! Best-effort `TCP_INFO` sampling for the HTTP transport's accepted
+//! connections. `TCP_INFO` isn't a stable cross-platform socket option (and
+//! `socket2` doesn't expose it), so this goes straight through
+//! `libc::getsockopt` and is Linux-only; everywhere else `sample` just
+//! returns `None`.
+
+/// A single `TCP_INFO` reading off a live connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TcpInfoSample {
+    pub rtt_us: u64,
+    pub retransmits: u32,
+}
+
+/// Reads `TCP_INFO` for `fd`. Returns `None` if the socket is no longer
+/// connected (or on any other `getsockopt` failure), which callers treat as
+/// "stop sampling this connection".
+#[cfg(target_os = "linux")]
+pub fn sample(fd: std::os::fd::RawFd) -> Option<TcpInfoSample> {
+    let mut info: libc::tcp_info = unsafe { std::mem::zeroed() };
+    let mut len = std::mem::size_of::<libc::tcp_info>() as libc::socklen_t;
+
+    let ret = unsafe {
+        libc::getsockopt(
+            fd,
+            libc::IPPROTO_TCP,
+            libc::TCP_INFO,
+            &mut info as *mut _ as *mut libc::c_void,
+            &mut len,
+        )
+    };
+
+    if ret != 0 {
+        return None;
+    }
+
+    Some(extract(&info))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn sample(_fd: std::os::fd::RawFd) -> Option<TcpInfoSample> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn extract(info: &libc::tcp_info) -> TcpInfoSample {
+    TcpInfoSample {
+        rtt_us: info.tcpi_rtt as u64,
+        retransmits: info.tcpi_retransmits as u32,
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extract_reads_rtt_and_retransmits_from_a_raw_tcp_info() {
+        let mut info: libc::tcp_info = unsafe { std::mem::zeroed() };
+        info.tcpi_rtt = 1234;
+        info.tcpi_retransmits = 2;
+
+        let sample = extract(&info);
+        assert_eq!(sample.rtt_us, 1234);
+        assert_eq!(sample.retransmits, 2);
+    }
+}